@@ -4,24 +4,43 @@ CREATE TABLE users (
     password_hash TEXT,
     ap_url TEXT NOT NULL UNIQUE,
     inbox_url TEXT NOT NULL,
+    shared_inbox_url TEXT,
     private_key TEXT,
     public_key TEXT,
+    key_id TEXT,
+    also_known_as TEXT NOT NULL DEFAULT '',
+    moved_to TEXT,
+    emojis TEXT NOT NULL DEFAULT '',
+    avatar_url TEXT,
+    header_url TEXT,
     display_name TEXT NOT NULL,
     bio TEXT DEFAULT '',
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     is_local BIGINT NOT NULL,
+    is_locked BIGINT NOT NULL DEFAULT 0,
     note_count BIGINT NOT NULL DEFAULT 0,
     following_count BIGINT NOT NULL DEFAULT 0,
     follower_count BIGINT NOT NULL DEFAULT 0
 );
 CREATE INDEX idx_users_is_local ON users(is_local);
+CREATE INDEX idx_users_key_id ON users(key_id);
+
+CREATE TABLE profile_fields (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_profile_fields_user_id ON profile_fields(user_id);
 
 CREATE TABLE follows (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     follower_id BIGINT NOT NULL,
     followee_id BIGINT NOT NULL,
     pending BIGINT NOT NULL,
+    ap_url TEXT,
     UNIQUE(follower_id, followee_id),
     FOREIGN KEY (follower_id) REFERENCES users(id) ON DELETE CASCADE,
     FOREIGN KEY (followee_id) REFERENCES users(id) ON DELETE CASCADE
@@ -35,21 +54,136 @@ CREATE table notes (
     boosted_username TEXT,
     boosted_created_at TEXT,
     content TEXT NOT NULL,
+    -- Legacy "type: url" list, moved to attachments by `tinyap migrate`
     attachments TEXT,
     parent_id BIGINT,
     parent_author_username TEXT,
+    quote_id BIGINT,
     created_at TEXT NOT NULL,
-    is_public BIGINT NOT NULL,
+    updated_at TEXT,
+    visibility TEXT NOT NULL DEFAULT 'public',
+    summary TEXT,
+    is_sensitive BIGINT NOT NULL DEFAULT 0,
+    emojis TEXT NOT NULL DEFAULT '',
+    conversation_id BIGINT,
     like_count BIGINT NOT NULL DEFAULT 0,
     boost_count BIGINT NOT NULL DEFAULT 0,
     FOREIGN KEY (author_id) REFERENCES users(id) ON DELETE CASCADE,
     FOREIGN KEY (boosted_id) REFERENCES notes(id) ON DELETE CASCADE,
-    FOREIGN KEY (parent_id) REFERENCES notes(id) ON DELETE CASCADE
+    FOREIGN KEY (parent_id) REFERENCES notes(id) ON DELETE CASCADE,
+    FOREIGN KEY (quote_id) REFERENCES notes(id) ON DELETE SET NULL
 );
 CREATE INDEX idx_notes_author_id ON notes(author_id);
 CREATE INDEX idx_notes_boosted_id ON notes(boosted_id);
 CREATE INDEX idx_notes_parent_id ON notes(parent_id);
 CREATE INDEX idx_notes_created_at_id ON notes(created_at, id);
+CREATE INDEX idx_notes_conversation_id ON notes(conversation_id);
+
+CREATE TABLE note_edits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id BIGINT NOT NULL,
+    content TEXT NOT NULL,
+    -- JSON list of the attachments of this version
+    attachments TEXT,
+    summary TEXT,
+    is_sensitive BIGINT NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_note_edits_note_id ON note_edits(note_id);
+
+CREATE TABLE media (
+    id BIGINT PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    note_id BIGINT,
+    file_name TEXT NOT NULL UNIQUE,
+    media_type TEXT NOT NULL,
+    description TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_media_note_id ON media(note_id);
+
+CREATE TABLE attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id BIGINT NOT NULL,
+    media_type TEXT,
+    url TEXT NOT NULL,
+    description TEXT,
+    blurhash TEXT,
+    width BIGINT,
+    height BIGINT,
+    focal_point TEXT,
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_attachments_note_id ON attachments(note_id);
+
+CREATE TABLE mentions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    UNIQUE(note_id, user_id),
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_mentions_user_id ON mentions(user_id);
+
+CREATE TABLE conversations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    participants TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE conversation_members (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    conversation_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    is_unread BIGINT NOT NULL DEFAULT 0,
+    UNIQUE(conversation_id, user_id),
+    FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_conversation_members_user_id ON conversation_members(user_id);
+
+CREATE TABLE hashtags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    UNIQUE(note_id, name),
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_hashtags_name ON hashtags(name);
+
+CREATE TABLE polls (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id BIGINT NOT NULL UNIQUE,
+    is_multiple BIGINT NOT NULL DEFAULT 0,
+    expires_at TEXT,
+    voters_count BIGINT NOT NULL DEFAULT 0,
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+
+CREATE TABLE poll_options (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    poll_id BIGINT NOT NULL,
+    position BIGINT NOT NULL,
+    title TEXT NOT NULL,
+    votes_count BIGINT NOT NULL DEFAULT 0,
+    UNIQUE(poll_id, position),
+    FOREIGN KEY (poll_id) REFERENCES polls(id) ON DELETE CASCADE
+);
+
+CREATE TABLE poll_votes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    poll_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    position BIGINT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE(poll_id, user_id, position),
+    FOREIGN KEY (poll_id) REFERENCES polls(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
 
 CREATE TABLE likes (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -60,6 +194,16 @@ CREATE TABLE likes (
     FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
 );
 
+CREATE TABLE pins (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id BIGINT NOT NULL,
+    note_id BIGINT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE(user_id, note_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+
 CREATE TABLE blocks (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     domain TEXT NOT NULL UNIQUE
@@ -79,6 +223,19 @@ CREATE TABLE notifications (
 CREATE INDEX idx_notifications_recipient_id ON notifications(recipient_id);
 CREATE INDEX idx_notifications_created_at ON notifications(created_at);
 
+-- Outbound delivery queue
+CREATE TABLE deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sender_id BIGINT NOT NULL,
+    inbox_url TEXT NOT NULL,
+    body TEXT NOT NULL,
+    attempts BIGINT NOT NULL DEFAULT 0,
+    next_attempt_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (sender_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_deliveries_next_attempt_at ON deliveries(next_attempt_at);
+
 -- Web session
 CREATE TABLE sessions (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
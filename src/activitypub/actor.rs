@@ -26,10 +26,32 @@ pub async fn get(State(state): State<AppState>, Path(username): Path<String>) ->
         "Content-Type",
         HeaderValue::from_static("application/activity+json"),
     );
-    let json_body = json!({
+    let also_known_as: Vec<&str> = user.also_known_as.lines().collect();
+    let fields = queries::profile_field::get_by_user(&state, user.id)
+        .await
+        .into_iter()
+        .map(|field| {
+            json!({
+                "type": "PropertyValue",
+                "name": field.name,
+                "value": field.value,
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut json_body = json!({
         "@context": [
             "https://www.w3.org/ns/activitystreams",
-            "https://w3id.org/security/v1"
+            "https://w3id.org/security/v1",
+            {
+                "alsoKnownAs": {"@id": "as:alsoKnownAs", "@type": "@id"},
+                "movedTo": {"@id": "as:movedTo", "@type": "@id"},
+                "manuallyApprovesFollowers": "as:manuallyApprovesFollowers",
+                "toot": "http://joinmastodon.org/ns#",
+                "featured": {"@id": "toot:featured", "@type": "@id"},
+                "schema": "http://schema.org#",
+                "PropertyValue": "schema:PropertyValue",
+                "value": "schema:value",
+            }
         ],
         "id": &user.ap_url,
         "type": "Person",
@@ -39,6 +61,12 @@ pub async fn get(State(state): State<AppState>, Path(username): Path<String>) ->
         "summary": &user.bio,
         "inbox": &user.inbox_url,
         "outbox": &utils::local_user_outbox_url(&state.domain, &user.username),
+        "followers": &utils::local_user_followers_url(&state.domain, &user.username),
+        "following": &utils::local_user_following_url(&state.domain, &user.username),
+        "featured": &utils::local_user_featured_url(&state.domain, &user.username),
+        "alsoKnownAs": also_known_as,
+        "attachment": fields,
+        "manuallyApprovesFollowers": user.is_locked == 1,
         "publicKey": {
             "id": &format!("{}#main-key", &user.ap_url),
             "owner": &user.ap_url,
@@ -48,6 +76,15 @@ pub async fn get(State(state): State<AppState>, Path(username): Path<String>) ->
             "sharedInbox": &format!("https://{}/inbox", &state.domain),
         }
     });
+    if let Some(avatar_url) = &user.avatar_url {
+        json_body["icon"] = json!({"type": "Image", "url": avatar_url});
+    }
+    if let Some(header_url) = &user.header_url {
+        json_body["image"] = json!({"type": "Image", "url": header_url});
+    }
+    if let Some(moved_to) = &user.moved_to {
+        json_body["movedTo"] = json!(moved_to);
+    }
 
     (json_headers, Json(json_body)).into_response()
 }
@@ -0,0 +1,56 @@
+use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::queries;
+use crate::back::utils;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
+use serde_json::{Value, json};
+
+pub async fn get(State(state): State<AppState>, Path(username): Path<String>) -> impl IntoResponse {
+    // Get user
+    let Some(user) = queries::user::get_by_username(&state, &username).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "user not found"})),
+        )
+            .into_response();
+    };
+    if user.is_local == 0 {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "user not found"})),
+        )
+            .into_response();
+    }
+
+    // Pinned notes, newest first
+    let notes = queries::timeline::get_pinned(&state, user.id, 0).await;
+    let mut items: Vec<Value> = vec![];
+    for note in &notes {
+        let Some(note) = queries::note::get_by_id(&state, note.id).await else {
+            continue;
+        };
+        let (note_object, _, _) = note::build_note_object(&state, &note, &user).await;
+        items.push(note_object);
+    }
+
+    let mut json_headers = HeaderMap::new();
+    json_headers.insert(
+        "Content-Type",
+        HeaderValue::from_static("application/activity+json"),
+    );
+    let json_body = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": utils::local_user_featured_url(&state.domain, &user.username),
+        "type": "OrderedCollection",
+        "totalItems": items.len(),
+        "orderedItems": items,
+    });
+
+    (json_headers, Json(json_body)).into_response()
+}
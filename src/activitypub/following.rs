@@ -0,0 +1,147 @@
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::back::queries::user::UserRecord;
+use crate::back::utils;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
+use serde_json::{Value, json};
+
+const FOLLOW_PAGE_SIZE: i64 = 40;
+
+#[derive(serde::Deserialize)]
+pub struct FollowingQuery {
+    pub page: Option<bool>,
+    pub max: Option<String>,
+}
+
+pub async fn get_following(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    Query(query): Query<FollowingQuery>,
+) -> impl IntoResponse {
+    // Get user
+    let Some(user) = get_local_user(&state, &username).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "user not found"})),
+        )
+            .into_response();
+    };
+    let collection_url = utils::local_user_following_url(&state.domain, &user.username);
+
+    // Get following
+    let following = if query.page.unwrap_or(false) && !state.config.hide_follow_lists {
+        let max_username = query.max.clone().unwrap_or_default();
+        Some(queries::follow::get_following(&state, user.id, &max_username, FOLLOW_PAGE_SIZE).await)
+    } else {
+        None
+    };
+
+    collection_json(
+        &state,
+        &collection_url,
+        user.following_count,
+        following,
+        query.max,
+    )
+    .into_response()
+}
+
+pub async fn get_followers(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    Query(query): Query<FollowingQuery>,
+) -> impl IntoResponse {
+    // Get user
+    let Some(user) = get_local_user(&state, &username).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "user not found"})),
+        )
+            .into_response();
+    };
+    let collection_url = utils::local_user_followers_url(&state.domain, &user.username);
+
+    // Get followers
+    let followers = if query.page.unwrap_or(false) && !state.config.hide_follow_lists {
+        let max_username = query.max.clone().unwrap_or_default();
+        Some(queries::follow::get_followers(&state, user.id, &max_username, FOLLOW_PAGE_SIZE).await)
+    } else {
+        None
+    };
+
+    collection_json(
+        &state,
+        &collection_url,
+        user.follower_count,
+        followers,
+        query.max,
+    )
+    .into_response()
+}
+
+async fn get_local_user(state: &AppState, username: &str) -> Option<UserRecord> {
+    let user = queries::user::get_by_username(state, username).await?;
+    if user.is_local == 0 {
+        return None;
+    }
+    Some(user)
+}
+
+fn collection_json(
+    state: &AppState,
+    collection_url: &str,
+    total_items: i64,
+    users: Option<Vec<queries::follow::FollowUserRecord>>,
+    max: Option<String>,
+) -> impl IntoResponse {
+    let mut json_headers = HeaderMap::new();
+    json_headers.insert(
+        "Content-Type",
+        HeaderValue::from_static("application/activity+json"),
+    );
+
+    // Collection, only totals if lists are hidden
+    let Some(users) = users else {
+        let mut json_body = json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "id": collection_url,
+            "type": "OrderedCollection",
+            "totalItems": total_items,
+        });
+        if !state.config.hide_follow_lists {
+            json_body["first"] = json!(format!("{}?page=true", collection_url));
+        }
+        return (json_headers, Json(json_body));
+    };
+
+    // Collection page
+    let page_url = match &max {
+        Some(max) => format!("{}?page=true&max={}", collection_url, max),
+        None => format!("{}?page=true", collection_url),
+    };
+    let items: Vec<Value> = users.iter().map(|user| json!(user.ap_url)).collect();
+    let mut json_body = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": &page_url,
+        "type": "OrderedCollectionPage",
+        "totalItems": total_items,
+        "partOf": collection_url,
+        "orderedItems": items,
+    });
+    if users.len() as i64 == FOLLOW_PAGE_SIZE
+        && let Some(last) = users.last()
+    {
+        json_body["next"] = json!(format!(
+            "{}?page=true&max={}",
+            collection_url, last.username
+        ));
+    }
+
+    (json_headers, Json(json_body))
+}
@@ -1,17 +1,23 @@
 mod accept;
+mod add;
 mod announce;
 mod create;
 mod delete;
 mod follow;
 mod like;
+mod migrate;
+mod proof;
+mod reject;
+mod remove;
 mod undo;
+mod update;
 
 use crate::back::init::AppState;
 use crate::back::queries;
 use crate::back::utils;
 
 use axum::{
-    Json,
+    body::Bytes,
     extract::{OriginalUri, State},
     http::{HeaderMap, StatusCode, Uri},
     response::IntoResponse,
@@ -24,7 +30,7 @@ use rsa::{
     signature::Verifier,
 };
 use serde_json::Value;
-use sha2::Sha256;
+use sha2::{Digest, Sha256, Sha512};
 use std::collections::HashMap;
 use time::{OffsetDateTime, PrimitiveDateTime};
 use tokio::task;
@@ -34,23 +40,41 @@ pub async fn post(
     State(state): State<AppState>,
     OriginalUri(uri): OriginalUri,
     headers: HeaderMap,
-    Json(activity): Json<Value>,
+    body: Bytes,
 ) -> impl IntoResponse {
+    // Verify digest before parsing
+    if let Err(e) = verify_digest(&headers, &body) {
+        println!("Digest verification failed: {}", e);
+        return (StatusCode::UNAUTHORIZED, e).into_response();
+    }
+
+    let Ok(activity) = serde_json::from_slice::<Value>(&body) else {
+        return (StatusCode::BAD_REQUEST, "invalid JSON").into_response();
+    };
     println!("Received activity: {}", activity);
 
     // Verify domain
-    let domain = match verify_domain(&state, &activity).await {
-        Ok(domain) => domain,
+    if let Err(e) = verify_domain(&state, &activity).await {
+        println!("Domain verification failed: {}", e);
+        return (StatusCode::FORBIDDEN, e).into_response();
+    }
+
+    // Verify signature
+    let signer = match verify_signature(&state, &uri, &headers).await {
+        Ok(signer) => signer,
         Err(e) => {
-            println!("Domain verification failed: {}", e);
-            return (StatusCode::FORBIDDEN, e).into_response();
+            println!("Signature verification failed: {}", e);
+            return (StatusCode::UNAUTHORIZED, e).into_response();
         }
     };
 
-    // Verify signature
-    if let Err(e) = verify_signature(&state, &uri, &headers, &domain).await {
-        println!("Signature verification failed: {}", e);
-        return (StatusCode::UNAUTHORIZED, e).into_response();
+    // Bind signer to actor, unless forwarded (e.g. by relays or instance actors)
+    // with an embedded proof by the actor
+    if activity["actor"].as_str() != Some(&signer)
+        && let Err(e) = proof::verify(&state, &activity).await
+    {
+        println!("Signer {} does not match actor: {}", signer, e);
+        return (StatusCode::UNAUTHORIZED, "signer does not match actor").into_response();
     }
 
     // Extract activity type
@@ -61,6 +85,7 @@ pub async fn post(
     match activity_type {
         "Follow" => follow::follow(&state, &activity).await,
         "Accept" => accept::follow(&state, &activity).await,
+        "Reject" => reject::follow(&state, &activity).await,
         "Like" => like::like(&state, &activity).await,
         "Announce" => announce::announce(&state, &activity).await,
         "Undo" => {
@@ -75,14 +100,38 @@ pub async fn post(
             }
         }
         "Create" => create::note(&state, &activity).await,
-        "Delete" => delete::note(&state, &activity).await,
+        "Delete" => {
+            let object_id = activity["object"]
+                .as_str()
+                .or(activity["object"]["id"].as_str());
+            if object_id.is_some() && object_id == activity["actor"].as_str() {
+                delete::actor(&state, &activity).await
+            } else {
+                delete::note(&state, &activity).await
+            }
+        }
+        "Move" => migrate::actor(&state, &activity).await,
+        "Add" => add::note(&state, &activity).await,
+        "Remove" => remove::note(&state, &activity).await,
+        "Update" => {
+            let Some(update_type) = activity["object"]["type"].as_str() else {
+                return (StatusCode::BAD_REQUEST, "missing update type").into_response();
+            };
+            match update_type {
+                "Note" | "Question" => update::note(&state, &activity).await,
+                "Application" | "Group" | "Organization" | "Person" | "Service" => {
+                    update::actor(&state, &activity).await
+                }
+                _ => {}
+            }
+        }
         _ => {}
     }
 
     (StatusCode::OK, "activity received").into_response()
 }
 
-async fn verify_domain(state: &AppState, activity: &Value) -> Result<String, String> {
+async fn verify_domain(state: &AppState, activity: &Value) -> Result<(), String> {
     // Extract actor domain
     let Some(actor) = activity["actor"].as_str() else {
         return Err("missing actor".to_string());
@@ -105,7 +154,65 @@ async fn verify_domain(state: &AppState, activity: &Value) -> Result<String, Str
         return Err("domain is blocked".to_string());
     }
 
-    Ok(domain.to_string())
+    Ok(())
+}
+
+fn verify_digest(headers: &HeaderMap, body: &[u8]) -> Result<(), String> {
+    let digest = headers.get("Digest");
+    let content_digest = headers.get("Content-Digest");
+    if digest.is_none() && content_digest.is_none() {
+        return Err("missing Digest header".to_string());
+    }
+
+    // Digest: SHA-256=<base64>
+    if let Some(digest) = digest {
+        let Ok(digest) = digest.to_str() else {
+            return Err("invalid Digest header".to_string());
+        };
+        let mut verified = false;
+        for part in digest.split(',') {
+            let Some((algorithm, value)) = part.trim().split_once('=') else {
+                return Err("invalid Digest header".to_string());
+            };
+            if algorithm.eq_ignore_ascii_case("SHA-256") {
+                if value != general_purpose::STANDARD.encode(Sha256::digest(body)) {
+                    return Err("Digest mismatch".to_string());
+                }
+                verified = true;
+            }
+        }
+        if !verified {
+            return Err("unsupported Digest algorithm".to_string());
+        }
+    }
+
+    // Content-Digest: sha-256=:<base64>: (RFC 9530)
+    if let Some(content_digest) = content_digest {
+        let Ok(content_digest) = content_digest.to_str() else {
+            return Err("invalid Content-Digest header".to_string());
+        };
+        let mut verified = false;
+        for part in content_digest.split(',') {
+            let Some((algorithm, value)) = part.trim().split_once('=') else {
+                return Err("invalid Content-Digest header".to_string());
+            };
+            let value = value.trim_matches(':');
+            let expected = match algorithm {
+                "sha-256" => general_purpose::STANDARD.encode(Sha256::digest(body)),
+                "sha-512" => general_purpose::STANDARD.encode(Sha512::digest(body)),
+                _ => continue,
+            };
+            if value != expected {
+                return Err("Content-Digest mismatch".to_string());
+            }
+            verified = true;
+        }
+        if !verified {
+            return Err("unsupported Content-Digest algorithm".to_string());
+        }
+    }
+
+    Ok(())
 }
 
 const SIGNATURE_MAX_AGE: i64 = 3600; // 1 hour
@@ -113,8 +220,7 @@ async fn verify_signature(
     state: &AppState,
     uri: &Uri,
     headers: &HeaderMap,
-    domain: &str,
-) -> Result<(), String> {
+) -> Result<String, String> {
     // Get Signature header
     let Some(sig_header) = headers.get("Signature") else {
         return Err("missing Signature header".to_string());
@@ -161,9 +267,6 @@ async fn verify_signature(
     let Some(key_id_domain) = key_id_url.domain() else {
         return Err("invalid keyId domain".to_string());
     };
-    if key_id_domain != domain {
-        return Err("mismatched keyId domain".to_string());
-    }
 
     let Some(signed_headers) = sig_map.get("headers") else {
         return Err("missing headers".to_string());
@@ -189,10 +292,14 @@ async fn verify_signature(
     };
     let mut signing_lines = Vec::new();
     let mut has_date = false;
+    let mut has_digest = false;
     for header in signed_headers {
         if header == "date" {
             has_date = true;
         }
+        if header == "digest" || header == "content-digest" {
+            has_digest = true;
+        }
 
         if header == "(request-target)" {
             signing_lines.push(format!("(request-target): post {}", path_and_query));
@@ -209,9 +316,22 @@ async fn verify_signature(
     if !has_date {
         return Err("missing signed header: date".to_string());
     }
+    if !has_digest {
+        return Err("missing signed header: digest".to_string());
+    }
     let signing_string = signing_lines.join("\n");
 
-    // Fetch public key
+    // Verify with cached public key
+    if let Some(user) = queries::user::get_by_key_id(state, key_id).await
+        && let Some(public_key_pem) = user.public_key
+        && verify_with_key(state, public_key_pem, &signing_string, &signature)
+            .await
+            .is_ok()
+    {
+        return Ok(user.ap_url);
+    }
+
+    // Fetch public key (not cached yet, or rotated)
     let Ok(res) = utils::signed_get(state, key_id).await else {
         return Err("failed to fetch public key".to_string());
     };
@@ -221,10 +341,41 @@ async fn verify_signature(
     let Some(public_key_pem) = res_json["publicKey"]["publicKeyPem"].as_str() else {
         return Err("missing public key".to_string());
     };
+    let public_key_pem = public_key_pem.to_string();
+
+    // Key owner, or the actor document holding it
+    let Some(owner) = res_json["publicKey"]["owner"]
+        .as_str()
+        .or(res_json["id"].as_str())
+    else {
+        return Err("missing public key owner".to_string());
+    };
+    if utils::url_host(owner) != key_id_domain {
+        return Err("mismatched public key owner domain".to_string());
+    }
+
+    verify_with_key(state, public_key_pem.clone(), &signing_string, &signature).await?;
+
+    // Cache public key on its owner
+    if let Some(user) = queries::user::get_by_ap_url(state, owner).await
+        && user.is_local == 0
+    {
+        queries::user::update_public_key(state, user.id, key_id, &public_key_pem).await;
+    }
+
+    Ok(owner.to_string())
+}
+
+async fn verify_with_key(
+    state: &AppState,
+    public_key_pem: String,
+    signing_string: &str,
+    signature: &Signature,
+) -> Result<(), String> {
+    let signing_string = signing_string.to_string();
+    let signature = signature.clone();
 
-    // Verify
     let _permit = state.sign_queue.acquire().await.unwrap();
-    let public_key_pem = public_key_pem.to_string();
     task::spawn_blocking(move || {
         let public_key_pem = public_key_pem.trim();
         let Ok(public_key) = RsaPublicKey::from_public_key_pem(public_key_pem) else {
@@ -241,3 +392,50 @@ async fn verify_signature(
     .unwrap()
     // Drop _permit here
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"type":"Create"}"#;
+
+    fn header_map(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn verify_digest_accepts_matching_digest() {
+        let digest = general_purpose::STANDARD.encode(Sha256::digest(BODY));
+        let headers = header_map("Digest", &format!("SHA-256={}", digest));
+        assert!(verify_digest(&headers, BODY).is_ok());
+    }
+
+    #[test]
+    fn verify_digest_accepts_matching_content_digest() {
+        let digest = general_purpose::STANDARD.encode(Sha512::digest(BODY));
+        let headers = header_map("Content-Digest", &format!("sha-512=:{}:", digest));
+        assert!(verify_digest(&headers, BODY).is_ok());
+    }
+
+    #[test]
+    fn verify_digest_rejects_other_body() {
+        let digest = general_purpose::STANDARD.encode(Sha256::digest(b"{}"));
+        let headers = header_map("Digest", &format!("SHA-256={}", digest));
+        assert!(verify_digest(&headers, BODY).is_err());
+
+        let headers = header_map("Content-Digest", &format!("sha-256=:{}:", digest));
+        assert!(verify_digest(&headers, BODY).is_err());
+    }
+
+    #[test]
+    fn verify_digest_rejects_missing_or_unsupported() {
+        assert!(verify_digest(&HeaderMap::new(), BODY).is_err());
+        assert!(verify_digest(&header_map("Digest", "MD5=abc"), BODY).is_err());
+        assert!(verify_digest(&header_map("Content-Digest", "md5=:abc:"), BODY).is_err());
+    }
+}
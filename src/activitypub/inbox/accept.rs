@@ -1,5 +1,6 @@
 use crate::back::follow;
 use crate::back::init::AppState;
+use crate::back::pin;
 use crate::back::queries;
 
 use serde_json::Value;
@@ -44,5 +45,8 @@ pub async fn follow(state: &AppState, activity: &Value) {
     };
     if existing.pending != 0 {
         follow::accept(state, object.id, actor.id).await;
+
+        // Show pins of the newly followed user
+        let _ = pin::fetch_remote(state, actor.id).await;
     }
 }
@@ -0,0 +1,26 @@
+use crate::back::init::AppState;
+use crate::back::pin;
+use crate::back::queries;
+
+use serde_json::Value;
+
+pub async fn note(state: &AppState, activity: &Value) {
+    // Extract actor and object
+    let Some(actor_ap_url) = activity["actor"].as_str() else {
+        return;
+    };
+    let Some(note_ap_url) = activity["object"]
+        .as_str()
+        .or(activity["object"]["id"].as_str())
+    else {
+        return;
+    };
+
+    // Get user
+    let Some(actor) = queries::user::get_by_ap_url(state, actor_ap_url).await else {
+        return;
+    };
+
+    // Pin, only on the featured collection
+    let _ = pin::add_remote(state, &actor, note_ap_url, &activity["target"]).await;
+}
@@ -1,66 +1,54 @@
+use crate::back::conversation;
 use crate::back::init::AppState;
 use crate::back::note;
 use crate::back::notification;
+use crate::back::poll;
 use crate::back::queries;
 use crate::back::user;
-use crate::back::utils;
 
 use serde_json::Value;
 
 pub async fn note(state: &AppState, activity: &Value) {
     let note_object = &activity["object"];
 
-    let Ok((note_ap_url, author_ap_url, content, attachments, in_reply_to, created_at, is_public)) =
-        note::parse_from_json(state, note_object).await
-    else {
+    // Poll votes are replies with a name and no content
+    if note_object["name"].is_string() && note_object["content"].is_null() {
+        return vote(state, activity).await;
+    }
+
+    let Ok(remote_note) = note::parse_from_json(state, note_object).await else {
         return;
     };
+    let note_ap_url = &remote_note.ap_url;
+    let author_ap_url = &remote_note.author_ap_url;
 
     // Check ownership
-    if author_ap_url != activity["actor"].as_str().unwrap_or("") {
+    if *author_ap_url != activity["actor"].as_str().unwrap_or("") {
         return;
     }
 
     // Check if already exists
-    let existing = queries::note::get_by_ap_url(state, &note_ap_url).await;
+    let existing = queries::note::get_by_ap_url(state, note_ap_url).await;
     if existing.is_some() {
         return;
     }
 
     // Create or update author
-    if let Some(author) = queries::user::get_by_ap_url(state, &author_ap_url).await {
+    if let Some(author) = queries::user::get_by_ap_url(state, author_ap_url).await {
         let _ = user::update_remote(state, &author.ap_url).await;
     } else {
-        if let Err(e) = user::add_remote(state, &author_ap_url).await {
+        if let Err(e) = user::add_remote(state, author_ap_url).await {
             println!("Failed to add remote user {}: {}", author_ap_url, e);
             return;
         }
     }
 
     // Fetch parent notes recursively
-    if let Some(in_reply_to) = &in_reply_to {
+    if let Some(in_reply_to) = &remote_note.in_reply_to {
         let _ = note::add_remote(state, in_reply_to, 0).await;
     }
 
-    let parent_id: Option<i64>;
-    let parent_author_username: Option<String>;
-    if let Some(in_reply_to) = &in_reply_to {
-        if let Some(parent) = queries::note::get_by_ap_url(state, in_reply_to).await {
-            parent_id = Some(parent.id);
-            let parent_author = queries::user::get_by_id(state, parent.author_id).await;
-            parent_author_username = Some(parent_author.username);
-        } else {
-            parent_id = None;
-            parent_author_username = None;
-        };
-    } else {
-        parent_id = None;
-        parent_author_username = None;
-    };
-
-    // Create note
-    let id = utils::gen_unique_id();
-    let author = queries::user::get_by_ap_url(state, &author_ap_url)
+    let author = queries::user::get_by_ap_url(state, author_ap_url)
         .await
         .unwrap();
 
@@ -97,22 +85,21 @@ pub async fn note(state: &AppState, activity: &Value) {
         vec![]
     };
 
-    let parent_author_uname = parent_author_username.clone().unwrap_or_default();
-
     // Create note
-    let _ = note::add(
-        state,
-        id,
-        &note_ap_url,
-        author.id,
-        &content,
-        attachments,
-        parent_id,
-        parent_author_username,
-        &created_at,
-        is_public,
-    )
-    .await;
+    let id = match note::add_parsed_remote(state, &remote_note, author.id, 0).await {
+        Ok(id) => id,
+        Err(e) => {
+            println!("Failed to add note {}: {}", note_ap_url, e);
+            return;
+        }
+    };
+    let note = queries::note::get_by_id(state, id).await.unwrap();
+    queries::user::update_date(state, author.id, &note.created_at).await;
+    if let Some(parent_id) = note.parent_id {
+        note::notify_reply(state, author.id, parent_id).await;
+    }
+    conversation::add_note(state, id).await;
+    let parent_author_uname = note.parent_author_username.unwrap_or_default();
 
     // Add notifications for mentions
     let mentioned_users = queries::user::get_by_username_in(state, &mentioned_usernames).await;
@@ -134,3 +121,41 @@ pub async fn note(state: &AppState, activity: &Value) {
         .await;
     }
 }
+
+async fn vote(state: &AppState, activity: &Value) {
+    let vote_object = &activity["object"];
+    let (Some(name), Some(in_reply_to), Some(actor)) = (
+        vote_object["name"].as_str(),
+        vote_object["inReplyTo"].as_str(),
+        activity["actor"].as_str(),
+    ) else {
+        return;
+    };
+
+    // Check ownership
+    if vote_object["attributedTo"].as_str() != Some(actor) {
+        return;
+    }
+
+    // Only local polls are counted here
+    let Some(note) = queries::note::get_by_ap_url(state, in_reply_to).await else {
+        return;
+    };
+    let author = queries::user::get_by_id(state, note.author_id).await;
+    if author.is_local == 0 {
+        return;
+    }
+
+    // Create voter if not exists
+    if queries::user::get_by_ap_url(state, actor).await.is_none()
+        && let Err(e) = user::add_remote(state, actor).await
+    {
+        println!("Failed to add remote user {}: {}", actor, e);
+        return;
+    }
+    let voter = queries::user::get_by_ap_url(state, actor).await.unwrap();
+
+    if let Err(e) = poll::add_remote_vote(state, voter.id, note.id, name).await {
+        println!("Failed to add vote from {}: {}", actor, e);
+    }
+}
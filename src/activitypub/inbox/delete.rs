@@ -1,6 +1,7 @@
 use crate::back::init::AppState;
 use crate::back::note;
 use crate::back::queries;
+use crate::back::user;
 
 use serde_json::Value;
 
@@ -30,3 +31,19 @@ pub async fn note(state: &AppState, activity: &Value) {
     // Delete
     note::delete(state, note.id, note.author_id).await;
 }
+
+pub async fn actor(state: &AppState, activity: &Value) {
+    // Get actor (object is checked to be the actor itself)
+    let Some(actor_ap_url) = activity["actor"].as_str() else {
+        return;
+    };
+    let Some(actor) = queries::user::get_by_ap_url(state, actor_ap_url).await else {
+        return;
+    };
+    if actor.is_local == 1 {
+        return;
+    }
+
+    // Delete actor and their content
+    user::delete(state, actor.id).await;
+}
@@ -2,9 +2,8 @@ use crate::back::follow;
 use crate::back::init::AppState;
 use crate::back::queries;
 use crate::back::user;
-use crate::back::utils;
 
-use serde_json::{Value, json};
+use serde_json::Value;
 
 pub async fn follow(state: &AppState, activity: &Value) {
     // Extract actor and object
@@ -19,7 +18,9 @@ pub async fn follow(state: &AppState, activity: &Value) {
     let Some(followee) = queries::user::get_by_ap_url(state, followee_ap_url).await else {
         return;
     };
-    let private_key = followee.private_key.unwrap();
+    if followee.is_local == 0 {
+        return;
+    }
 
     // Create locally-stored remote user if not exists
     let follower = {
@@ -39,28 +40,25 @@ pub async fn follow(state: &AppState, activity: &Value) {
 
     // Create follow if not exists
     let existing = queries::follow::get(state, follower.id, followee.id).await;
-    if existing.is_none() {
-        let _ = follow::follow(state, follower.id, followee.id).await;
-        let _ = follow::accept(state, follower.id, followee.id).await;
+    let pending = match existing {
+        Some(existing) => existing.pending == 1,
+        None => {
+            let _ = follow::follow(state, follower.id, followee.id).await;
+            true
+        }
+    };
+    if let Some(follow_ap_url) = activity["id"].as_str() {
+        queries::follow::update_ap_url(state, follower.id, followee.id, follow_ap_url).await;
     }
 
-    // Return Accept activity
-    let accept_id = format!("{}#accept-{}", followee.ap_url, utils::gen_unique_id());
-    let accept_activity = json!({
-        "@context": "https://www.w3.org/ns/activitystreams",
-        "id": accept_id,
-        "type": "Accept",
-        "actor": followee.ap_url,
-        "object": activity,
-    });
-    let json_body = accept_activity.to_string();
+    // Locked users approve follow requests themselves
+    if pending {
+        if followee.is_locked == 1 {
+            return;
+        }
+        follow::accept(state, follower.id, followee.id).await;
+    }
 
-    utils::signed_deliver(
-        state,
-        &followee.ap_url,
-        &private_key,
-        &follower.inbox_url,
-        &json_body,
-    )
-    .await;
+    // Return Accept activity
+    follow::deliver_accept(state, follower.id, followee.id).await;
 }
@@ -0,0 +1,51 @@
+use crate::back::follow;
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::back::user;
+
+use serde_json::Value;
+
+pub async fn actor(state: &AppState, activity: &Value) {
+    // Extract actor, object and target
+    let Some(actor_ap_url) = activity["actor"].as_str() else {
+        return;
+    };
+    let object_ap_url = activity["object"]
+        .as_str()
+        .or(activity["object"]["id"].as_str());
+    if object_ap_url != Some(actor_ap_url) {
+        return;
+    }
+    let Some(target_ap_url) = activity["target"]
+        .as_str()
+        .or(activity["target"]["id"].as_str())
+    else {
+        return;
+    };
+
+    // Get old account
+    let Some(old) = queries::user::get_by_ap_url(state, actor_ap_url).await else {
+        return;
+    };
+    if old.is_local == 1 {
+        return;
+    }
+
+    // Target must list the old account as an alias
+    let Ok(target) = user::resolve(state, target_ap_url).await else {
+        return;
+    };
+    if target.id == old.id
+        || !target
+            .also_known_as
+            .lines()
+            .any(|alias| alias == old.ap_url)
+    {
+        return;
+    }
+
+    queries::user::update_moved_to(state, old.id, Some(&target.ap_url)).await;
+
+    // Re-point local followers
+    follow::move_followers(state, old.id, target.id).await;
+}
@@ -0,0 +1,112 @@
+use crate::back::init::AppState;
+use crate::back::utils;
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+// Embedded Object Integrity Proof (FEP-8b32, eddsa-jcs-2022)
+pub async fn verify(state: &AppState, activity: &Value) -> Result<(), String> {
+    let Some(actor) = activity["actor"].as_str() else {
+        return Err("missing actor".to_string());
+    };
+
+    // Extract proof
+    let proof = &activity["proof"];
+    if proof["type"] != "DataIntegrityProof" || proof["cryptosuite"] != "eddsa-jcs-2022" {
+        return Err("missing or unsupported proof".to_string());
+    }
+    if proof["proofPurpose"] != "assertionMethod" {
+        return Err("invalid proof purpose".to_string());
+    }
+    let Some(verification_method) = proof["verificationMethod"].as_str() else {
+        return Err("missing verification method".to_string());
+    };
+    let Some(proof_value) = proof["proofValue"].as_str() else {
+        return Err("missing proof value".to_string());
+    };
+    let Some(signature) = decode_multibase(proof_value) else {
+        return Err("invalid proof value".to_string());
+    };
+    let Ok(signature) = Signature::from_slice(&signature) else {
+        return Err("invalid proof value".to_string());
+    };
+
+    // Fetch public key
+    let public_key = fetch_key(state, verification_method, actor).await?;
+
+    // Hash proof options and document separately
+    let mut document = activity.clone();
+    document.as_object_mut().unwrap().remove("proof");
+    let mut options = proof.clone();
+    options.as_object_mut().unwrap().remove("proofValue");
+    if let Some(context) = activity.get("@context") {
+        options["@context"] = context.clone();
+    }
+    let (Ok(options), Ok(document)) = (serde_jcs::to_vec(&options), serde_jcs::to_vec(&document))
+    else {
+        return Err("failed to canonicalize".to_string());
+    };
+    let mut hash_data = Sha256::digest(&options).to_vec();
+    hash_data.extend_from_slice(&Sha256::digest(&document));
+
+    // Verify
+    match public_key.verify(&hash_data, &signature) {
+        Ok(_) => Ok(()),
+        Err(_) => Err("proof verification failed".to_string()),
+    }
+}
+
+async fn fetch_key(
+    state: &AppState,
+    verification_method: &str,
+    actor: &str,
+) -> Result<VerifyingKey, String> {
+    if utils::url_host(verification_method) != utils::url_host(actor) {
+        return Err("mismatched verification method domain".to_string());
+    }
+
+    let Ok(res) = utils::signed_get(state, verification_method).await else {
+        return Err("failed to fetch verification method".to_string());
+    };
+    let Ok(res_json) = res.json::<Value>().await else {
+        return Err("invalid verification method response".to_string());
+    };
+
+    // Either the Multikey itself or the actor holding it
+    let multikey = if res_json["type"] == "Multikey" {
+        &res_json
+    } else {
+        res_json["assertionMethod"]
+            .as_array()
+            .and_then(|methods| methods.iter().find(|m| m["id"] == verification_method))
+            .unwrap_or(&Value::Null)
+    };
+    if multikey["controller"].as_str() != Some(actor) {
+        return Err("verification method is not controlled by actor".to_string());
+    }
+
+    // Ed25519 public key with multicodec prefix 0xed01
+    let Some(public_key) = multikey["publicKeyMultibase"]
+        .as_str()
+        .and_then(decode_multibase)
+    else {
+        return Err("invalid public key".to_string());
+    };
+    let Some(public_key) = public_key.strip_prefix(&[0xed, 0x01]) else {
+        return Err("unsupported public key type".to_string());
+    };
+    let Ok(public_key) = <[u8; 32]>::try_from(public_key) else {
+        return Err("invalid public key".to_string());
+    };
+    match VerifyingKey::from_bytes(&public_key) {
+        Ok(public_key) => Ok(public_key),
+        Err(_) => Err("invalid public key".to_string()),
+    }
+}
+
+// base58btc only
+fn decode_multibase(value: &str) -> Option<Vec<u8>> {
+    let value = value.strip_prefix('z')?;
+    bs58::decode(value).into_vec().ok()
+}
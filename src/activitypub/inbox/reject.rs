@@ -0,0 +1,49 @@
+use crate::back::follow;
+use crate::back::init::AppState;
+use crate::back::queries;
+
+use serde_json::Value;
+
+pub async fn follow(state: &AppState, activity: &Value) {
+    // Extract actor and object
+    // actor:  remote user who rejects follow request or removes follower
+    // object: local user who sent follow request
+    let Some(actor_ap_url) = activity["actor"].as_str() else {
+        return;
+    };
+
+    if activity["object"]["type"]
+        .as_str()
+        .is_some_and(|object_type| object_type != "Follow")
+    {
+        return;
+    }
+
+    let object_ap_url = if let Some(obj) = activity["object"].as_str() {
+        obj
+    } else if let Some(obj) = activity["object"]["actor"].as_str() {
+        obj
+    } else if let Some(obj) = activity["object"]["actor"]["id"].as_str() {
+        obj
+    } else {
+        return;
+    };
+
+    // Remove fragment from URL
+    let object_ap_url = object_ap_url
+        .split_once('#')
+        .map(|(url, _)| url)
+        .unwrap_or(object_ap_url);
+
+    // Get user
+    let Some(actor) = queries::user::get_by_ap_url(state, actor_ap_url).await else {
+        return;
+    };
+
+    let Some(object) = queries::user::get_by_ap_url(state, object_ap_url).await else {
+        return;
+    };
+
+    // Delete follow, pending or accepted, and fix counters
+    let _ = follow::unfollow(state, object.id, actor.id).await;
+}
@@ -0,0 +1,80 @@
+use crate::back::attachment;
+use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::pin;
+use crate::back::poll;
+use crate::back::queries;
+use crate::back::quote;
+use crate::back::user;
+
+use serde_json::Value;
+
+pub async fn note(state: &AppState, activity: &Value) {
+    let note_object = &activity["object"];
+
+    let Ok(remote_note) = note::parse_from_json(state, note_object).await else {
+        return;
+    };
+    let note_ap_url = remote_note.ap_url;
+    let author_ap_url = remote_note.author_ap_url;
+
+    // Check ownership
+    if author_ap_url != activity["actor"].as_str().unwrap_or("") {
+        return;
+    }
+
+    // Get note
+    let Some(existing) = queries::note::get_by_ap_url(state, &note_ap_url).await else {
+        return;
+    };
+
+    // Check stored author
+    let author = queries::user::get_by_id(state, existing.author_id).await;
+    if author.ap_url != author_ap_url {
+        return;
+    }
+
+    // Update
+    let _ = note::update(
+        state,
+        existing.id,
+        &remote_note.content,
+        remote_note.summary,
+        remote_note.is_sensitive,
+    )
+    .await;
+    note::add_remote_mentions(state, existing.id, &remote_note.mentions).await;
+    attachment::add_remote(state, existing.id, &remote_note.attachments).await;
+    queries::note::update_emojis(state, existing.id, &remote_note.emojis).await;
+    note::add_remote_hashtags(
+        state,
+        existing.id,
+        &remote_note.content,
+        &remote_note.hashtags,
+    )
+    .await;
+    if let Some(remote_poll) = &remote_note.poll {
+        poll::add_remote(state, existing.id, remote_poll).await;
+    }
+    if let Some(quote_url) = &remote_note.quote_url {
+        quote::add_remote(state, existing.id, quote_url, 0).await;
+    }
+}
+
+pub async fn actor(state: &AppState, activity: &Value) {
+    // Extract object
+    let Some(object_ap_url) = activity["object"]["id"].as_str() else {
+        return;
+    };
+
+    // Check ownership
+    if object_ap_url != activity["actor"].as_str().unwrap_or("") {
+        return;
+    }
+
+    // Update if exists
+    if let Some(actor) = queries::user::get_by_ap_url(state, object_ap_url).await {
+        let _ = user::update_remote(state, object_ap_url).await;
+        let _ = pin::fetch_remote(state, actor.id).await;
+    }
+}
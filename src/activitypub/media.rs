@@ -0,0 +1,36 @@
+use crate::back::init::AppState;
+use crate::back::queries;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
+
+pub async fn get(
+    State(state): State<AppState>,
+    Path(file_name): Path<String>,
+) -> impl IntoResponse {
+    // Get media
+    let Some(media) = queries::media::get_by_file_name(&state, &file_name).await else {
+        return (StatusCode::NOT_FOUND, "media not found").into_response();
+    };
+
+    // Read file
+    let path = std::path::Path::new(&state.config.media_dir).join(&media.file_name);
+    let Ok(data) = tokio::fs::read(path).await else {
+        return (StatusCode::NOT_FOUND, "media not found").into_response();
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "Content-Type",
+        HeaderValue::from_str(&media.media_type).unwrap(),
+    );
+    headers.insert(
+        "Cache-Control",
+        HeaderValue::from_static("public, max-age=31536000, immutable"),
+    );
+
+    (headers, data).into_response()
+}
@@ -1,5 +1,9 @@
 pub mod actor;
+pub mod featured;
+pub mod following;
 pub mod inbox;
+pub mod media;
 pub mod nodeinfo;
 pub mod note;
+pub mod outbox;
 pub mod webfinger;
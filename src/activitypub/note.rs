@@ -1,6 +1,6 @@
 use crate::back::init::AppState;
+use crate::back::note;
 use crate::back::queries;
-use crate::back::utils;
 
 use axum::{
     Json,
@@ -20,48 +20,23 @@ pub async fn get(State(state): State<AppState>, Path(id): Path<i64>) -> impl Int
             .into_response();
     };
 
-    // Check if public
-    if note.is_public == 0 {
+    // Followers-only and direct notes are not served to unauthenticated fetches
+    if !note::can_view(&state, &note, None).await {
         return (
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "note is private"})),
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "note not found"})),
         )
             .into_response();
     }
 
-    let parent_ap_url = if let Some(parent_id) = note.parent_id {
-        let Some(parent) = queries::note::get_by_id(&state, parent_id).await else {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(json!({"error": "parent note not found"})),
-            )
-                .into_response();
-        };
-        Some(parent.ap_url)
-    } else {
-        None
-    };
-
-    // Get author
-    let author = queries::user::get_by_id(&state, note.author_id).await;
-
     // Response
     let mut json_headers = HeaderMap::new();
     json_headers.insert(
         "Content-Type",
         HeaderValue::from_static("application/activity+json"),
     );
-    let json_body = json!({
-        "@context": "https://www.w3.org/ns/activitystreams",
-        "id": &note.ap_url,
-        "type": "Note",
-        "url": &utils::note_url(&state.domain, &author.username, note.id),
-        "attributedTo": &author.ap_url,
-        "content": &note.content,
-        "inReplyTo": &parent_ap_url,
-        "published": &note.created_at,
-        "to": ["https://www.w3.org/ns/activitystreams#Public"],
-    });
+    let author = queries::user::get_by_id(&state, note.author_id).await;
+    let (json_body, _, _) = note::build_note_object(&state, &note, &author).await;
 
     (json_headers, Json(json_body)).into_response()
 }
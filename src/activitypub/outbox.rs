@@ -0,0 +1,123 @@
+use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::queries;
+use crate::back::utils;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
+use serde_json::{Value, json};
+
+const OUTBOX_PAGE_SIZE: i64 = 20;
+
+#[derive(serde::Deserialize)]
+pub struct OutboxQuery {
+    pub page: Option<bool>,
+    pub until: Option<i64>,
+}
+
+pub async fn get(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    Query(query): Query<OutboxQuery>,
+) -> impl IntoResponse {
+    // Get user
+    let Some(user) = queries::user::get_by_username(&state, &username).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "user not found"})),
+        )
+            .into_response();
+    };
+    if user.is_local == 0 {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "user not found"})),
+        )
+            .into_response();
+    }
+
+    let mut json_headers = HeaderMap::new();
+    json_headers.insert(
+        "Content-Type",
+        HeaderValue::from_static("application/activity+json"),
+    );
+    let outbox_url = utils::local_user_outbox_url(&state.domain, &user.username);
+
+    // Collection
+    if !query.page.unwrap_or(false) {
+        let json_body = json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "id": &outbox_url,
+            "type": "OrderedCollection",
+            "totalItems": user.note_count,
+            "first": &format!("{}?page=true", &outbox_url),
+        });
+        return (json_headers, Json(json_body)).into_response();
+    }
+
+    // Collection page
+    let (until_date, until_id) = utils::extract_until_id(&state, query.until).await;
+    let notes =
+        queries::timeline::get_user(&state, user.id, 0, &until_date, until_id, OUTBOX_PAGE_SIZE)
+            .await;
+
+    let followers_url = utils::local_user_followers_url(&state.domain, &user.username);
+    let mut items: Vec<Value> = vec![];
+    for note in &notes {
+        let ap_url = utils::local_note_ap_url(&state.domain, note.id);
+        if let Some(boosted_id) = note.boosted_id {
+            // Announce
+            let Some(boosted) = queries::note::get_by_id(&state, boosted_id).await else {
+                continue;
+            };
+            let (to, cc) = note::addressing(&note.visibility, followers_url.clone(), vec![]);
+            items.push(json!({
+                "id": &format!("{}#announce", &ap_url),
+                "type": "Announce",
+                "actor": &user.ap_url,
+                "published": &note.created_at,
+                "to": to,
+                "cc": cc,
+                "object": &boosted.ap_url,
+            }));
+        } else {
+            // Create
+            let Some(note) = queries::note::get_by_id(&state, note.id).await else {
+                continue;
+            };
+            let (note_object, _, _) = note::build_note_object(&state, &note, &user).await;
+            items.push(json!({
+                "id": &format!("{}#create", &ap_url),
+                "type": "Create",
+                "actor": &user.ap_url,
+                "published": &note.created_at,
+                "to": note_object["to"],
+                "cc": note_object["cc"],
+                "object": note_object,
+            }));
+        }
+    }
+
+    let page_url = match query.until {
+        Some(until) => format!("{}?page=true&until={}", &outbox_url, until),
+        None => format!("{}?page=true", &outbox_url),
+    };
+    let mut json_body = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": &page_url,
+        "type": "OrderedCollectionPage",
+        "partOf": &outbox_url,
+        "orderedItems": items,
+    });
+    if notes.len() as i64 == OUTBOX_PAGE_SIZE
+        && let Some(last_note) = notes.last()
+    {
+        json_body["next"] = json!(format!("{}?page=true&until={}", &outbox_url, last_note.id));
+    }
+
+    (json_headers, Json(json_body)).into_response()
+}
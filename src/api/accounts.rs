@@ -1,3 +1,6 @@
+use crate::api::auth::MaybeOAuthUser;
+use crate::api::polls::add_polls;
+use crate::api::quotes::add_quotes;
 use crate::api::timeline::{build_link_header, timeline_json};
 use crate::back::init::AppState;
 use crate::back::queries;
@@ -11,6 +14,7 @@ use axum::{
 };
 use serde_json::{Value, json};
 
+#[allow(clippy::too_many_arguments)]
 pub fn account_json(
     state: &AppState,
     username: &str,
@@ -21,15 +25,26 @@ pub fn account_json(
     following_count: i64,
     note_count: i64,
     updated_at: &str,
+    emojis: &str,
+    avatar_url: Option<&str>,
+    header_url: Option<&str>,
 ) -> Value {
     let avatar_placeholder = format!("https://{}/static/missing.png", state.domain);
+    let avatar_url = avatar_url
+        .map(|url| url.to_string())
+        .unwrap_or(avatar_placeholder.clone());
+    let header_url = header_url
+        .map(|url| url.to_string())
+        .unwrap_or(avatar_placeholder);
     json!({
         "id": username,
         "username": username,
         "acct": username,
         "display_name": display_name,
-        "avatar": avatar_placeholder,
-        "header": avatar_placeholder,
+        "avatar": avatar_url,
+        "avatar_static": avatar_url,
+        "header": header_url,
+        "header_static": header_url,
         "created_at": created_at,
         "note": bio,
         "followers_count": follower_count,
@@ -38,7 +53,7 @@ pub fn account_json(
         "last_status_at": updated_at,
         "fields": [],
         "locked": false,
-        "emojis": [],
+        "emojis": utils::emojis_to_value(emojis),
         "url": utils::user_url(&state.domain, username),
         "bot": false,
         "source": {
@@ -51,13 +66,37 @@ pub fn account_json(
     })
 }
 
+// Fields are only shown where the full account is requested
+pub async fn add_fields(state: &AppState, account_json: &mut Value, user_id: i64) {
+    let fields = queries::profile_field::get_by_user(state, user_id).await;
+    account_json["fields"] = fields
+        .iter()
+        .map(|field| {
+            json!({
+                "name": field.name,
+                "value": field.value,
+                "verified_at": null,
+            })
+        })
+        .collect();
+    account_json["source"]["fields"] = fields
+        .iter()
+        .map(|field| {
+            json!({
+                "name": field.name,
+                "value": utils::strip_content(state, &field.value),
+            })
+        })
+        .collect();
+}
+
 pub async fn get(State(state): State<AppState>, Path(username): Path<String>) -> Json<Value> {
     // Get user
     let Some(user) = queries::user::get_by_username(&state, &username).await else {
         return Json(json!({"error": "User not found"}));
     };
 
-    let account_json = account_json(
+    let mut account_json = account_json(
         &state,
         &user.username,
         &user.display_name,
@@ -67,7 +106,12 @@ pub async fn get(State(state): State<AppState>, Path(username): Path<String>) ->
         user.following_count,
         user.note_count,
         &user.updated_at,
+        &user.emojis,
+        user.avatar_url.as_deref(),
+        user.header_url.as_deref(),
     );
+    account_json["locked"] = json!(user.is_locked == 1);
+    add_fields(&state, &mut account_json, user.id).await;
 
     Json(account_json)
 }
@@ -83,14 +127,8 @@ pub async fn get_statuses(
     State(state): State<AppState>,
     Path(username): Path<String>,
     Query(query): Query<StatusesQuery>,
+    auth_user: MaybeOAuthUser,
 ) -> impl IntoResponse {
-    // Return empty if pinned is true
-    if let Some(pinned) = query.pinned
-        && pinned
-    {
-        return Json(json!([])).into_response();
-    }
-
     // Extract limit
     let limit = query.limit.unwrap_or(20);
     let limit = if limit > 40 { 40 } else { limit };
@@ -103,8 +141,33 @@ pub async fn get_statuses(
         return Json(json!({"error": "User not found"})).into_response();
     };
 
+    // Pinned notes are not paginated
+    if query.pinned.unwrap_or(false) {
+        if query.max_id.is_some() {
+            return Json(json!([])).into_response();
+        }
+        let notes = queries::timeline::get_pinned(&state, user.id, auth_user.id.unwrap_or(0)).await;
+        let mut notes_json = timeline_json(&state, notes).await;
+        if let Some(statuses) = notes_json.as_array_mut() {
+            for status in statuses {
+                status["pinned"] = json!(true);
+            }
+        }
+        add_polls(&state, &mut notes_json, auth_user.id).await;
+        add_quotes(&state, &mut notes_json).await;
+        return Json(notes_json).into_response();
+    }
+
     // Get notes by user
-    let notes = queries::timeline::get_user(&state, user.id, &until_date, until_id, limit).await;
+    let notes = queries::timeline::get_user(
+        &state,
+        user.id,
+        auth_user.id.unwrap_or(0),
+        &until_date,
+        until_id,
+        limit,
+    )
+    .await;
 
     let mut headers = HeaderMap::new();
     if let (Some(first), Some(last)) = (notes.first(), notes.last()) {
@@ -116,7 +179,9 @@ pub async fn get_statuses(
         );
         headers.insert("Link", link.parse().unwrap());
     }
-    let notes_json = timeline_json(&state, notes);
+    let mut notes_json = timeline_json(&state, notes).await;
+    add_polls(&state, &mut notes_json, auth_user.id).await;
+    add_quotes(&state, &mut notes_json).await;
 
     (headers, Json(notes_json)).into_response()
 }
@@ -33,3 +33,22 @@ impl FromRequestParts<AppState> for OAuthUser {
         Ok(OAuthUser { id: token.user_id })
     }
 }
+
+pub struct MaybeOAuthUser {
+    pub id: Option<i64>,
+}
+
+impl FromRequestParts<AppState> for MaybeOAuthUser {
+    type Rejection = ();
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user = OAuthUser::from_request_parts(parts, state).await;
+
+        Ok(MaybeOAuthUser {
+            id: user.ok().map(|user| user.id),
+        })
+    }
+}
@@ -1,6 +1,9 @@
 use crate::api::accounts::account_json;
-use crate::api::statuses::status_json;
+use crate::api::auth::MaybeOAuthUser;
+use crate::api::statuses::{add_tags, add_tags_to_all, status_json};
+use crate::back::attachment;
 use crate::back::init::AppState;
+use crate::back::note;
 use crate::back::queries;
 use crate::back::utils;
 
@@ -10,22 +13,37 @@ use axum::{
 };
 use serde_json::{Value, json};
 
-pub async fn get(State(state): State<AppState>, Path(id): Path<i64>) -> Json<Value> {
+pub async fn get(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: MaybeOAuthUser,
+) -> Json<Value> {
     let Some(note) = queries::note::get_by_id(&state, id).await else {
         return Json(json!({
             "error": "Note not found"
         }));
     };
+    if !note::can_view(&state, &note, user.id).await {
+        return Json(json!({
+            "error": "Note not found"
+        }));
+    }
 
     // Get ancestor (parent)
-    let ancestor = if let Some(parent_id) = note.parent_id {
+    let mut ancestor = if let Some(parent_id) = note.parent_id
+        && let Some(parent) = queries::note::get_by_id(&state, parent_id).await
+        && note::can_view(&state, &parent, user.id).await
+    {
         queries::note::get_with_author_by_id(&state, parent_id).await
     } else {
         None
     };
 
+    if let Some(ancestor) = &mut ancestor {
+        attachment::fill_one(&state, ancestor).await;
+    }
     let ancestors_json = if let Some(ancestor) = ancestor {
-        let attachments = utils::attachments_to_value(&state, &ancestor.attachments);
+        let attachments = utils::attachments_to_value(&ancestor.attachments);
         let account_json = account_json(
             &state,
             &ancestor.username,
@@ -36,8 +54,11 @@ pub async fn get(State(state): State<AppState>, Path(id): Path<i64>) -> Json<Val
             0,
             0,
             &ancestor.created_at,
+            &ancestor.author_emojis,
+            ancestor.author_avatar_url.as_deref(),
+            ancestor.author_header_url.as_deref(),
         );
-        let status_json = status_json(
+        let mut status_json = status_json(
             &state,
             ancestor.id,
             &ancestor.username,
@@ -54,7 +75,12 @@ pub async fn get(State(state): State<AppState>, Path(id): Path<i64>) -> Json<Val
             false,
             ancestor.parent_id,
             ancestor.parent_author_username,
+            &ancestor.visibility,
+            ancestor.summary.clone(),
+            ancestor.is_sensitive == 1,
+            &ancestor.emojis,
         );
+        add_tags(&state, &mut status_json).await;
 
         json!([status_json])
     } else {
@@ -62,12 +88,14 @@ pub async fn get(State(state): State<AppState>, Path(id): Path<i64>) -> Json<Val
     };
 
     // Get descendants (replies)
-    let descendants = queries::note::get_replies_by_parent_id(&state, note.id).await;
+    let mut descendants =
+        queries::note::get_replies_by_parent_id(&state, note.id, user.id.unwrap_or(0)).await;
+    attachment::fill(&state, &mut descendants).await;
 
-    let descendants_json: Value = descendants
+    let mut descendants_json: Value = descendants
         .into_iter()
         .map(|descendant| {
-            let attachments = utils::attachments_to_value(&state, &descendant.attachments);
+            let attachments = utils::attachments_to_value(&descendant.attachments);
             let account_json = account_json(
                 &state,
                 &descendant.username,
@@ -78,6 +106,9 @@ pub async fn get(State(state): State<AppState>, Path(id): Path<i64>) -> Json<Val
                 0,
                 0,
                 &descendant.created_at,
+                &descendant.author_emojis,
+                descendant.author_avatar_url.as_deref(),
+                descendant.author_header_url.as_deref(),
             );
             status_json(
                 &state,
@@ -96,9 +127,14 @@ pub async fn get(State(state): State<AppState>, Path(id): Path<i64>) -> Json<Val
                 false,
                 descendant.parent_id,
                 descendant.parent_author_username,
+                &descendant.visibility,
+                descendant.summary.clone(),
+                descendant.is_sensitive == 1,
+                &descendant.emojis,
             )
         })
         .collect();
+    add_tags_to_all(&state, &mut descendants_json).await;
 
     Json(json!({
         "ancestors": ancestors_json,
@@ -0,0 +1,98 @@
+use crate::api::accounts::account_json;
+use crate::api::auth::OAuthUser;
+use crate::api::timeline::{TimelineQuery, extract_limit, timeline_json};
+use crate::back::conversation;
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::back::queries::conversation::ConversationWithMemberRecord;
+use crate::back::utils;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde_json::{Value, json};
+
+async fn conversation_json(
+    state: &AppState,
+    conversation: ConversationWithMemberRecord,
+    user_id: i64,
+) -> Value {
+    let accounts_json: Vec<Value> =
+        conversation::get_participants(state, &conversation.participants, user_id)
+            .await
+            .into_iter()
+            .map(|participant| {
+                account_json(
+                    state,
+                    &participant.username,
+                    &participant.display_name,
+                    &participant.created_at,
+                    &participant.bio,
+                    participant.follower_count,
+                    participant.following_count,
+                    participant.note_count,
+                    &participant.updated_at,
+                    &participant.emojis,
+                    participant.avatar_url.as_deref(),
+                    participant.header_url.as_deref(),
+                )
+            })
+            .collect();
+
+    let last_note = queries::note::get_with_author_by_id(state, conversation.last_note_id).await;
+    let last_status_json = timeline_json(state, last_note.into_iter().collect()).await;
+
+    json!({
+        "id": conversation.id.to_string(),
+        "unread": conversation.is_unread == 1,
+        "accounts": accounts_json,
+        "last_status": last_status_json.get(0),
+    })
+}
+
+pub async fn get(
+    State(state): State<AppState>,
+    Query(query): Query<TimelineQuery>,
+    user: OAuthUser,
+) -> Json<Value> {
+    let limit = extract_limit(query.limit).await;
+    let (until_date, until_id) = utils::extract_until_id(&state, query.max_id).await;
+
+    let conversations =
+        queries::conversation::get_by_user(&state, user.id, &until_date, until_id, limit).await;
+
+    let mut conversations_json = vec![];
+    for conversation in conversations {
+        conversations_json.push(conversation_json(&state, conversation, user.id).await);
+    }
+
+    Json(json!(conversations_json))
+}
+
+pub async fn post_read(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: OAuthUser,
+) -> Json<Value> {
+    // Mark as read
+    queries::conversation::update_read(&state, id, user.id).await;
+
+    let Some(conversation) = queries::conversation::get_by_id_and_user(&state, id, user.id).await
+    else {
+        return Json(json!({"error": "Conversation not found"}));
+    };
+
+    Json(conversation_json(&state, conversation, user.id).await)
+}
+
+pub async fn delete(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: OAuthUser,
+) -> Json<Value> {
+    // Remove from the user's conversations
+    queries::conversation::delete_member(&state, id, user.id).await;
+
+    Json(json!({}))
+}
@@ -1,6 +1,6 @@
 use crate::api::accounts::account_json;
 use crate::api::auth::OAuthUser;
-use crate::api::statuses::status_json;
+use crate::api::statuses::{add_tags, status_json};
 use crate::back::init::AppState;
 use crate::back::like;
 use crate::back::queries;
@@ -49,8 +49,11 @@ pub async fn post_favourite(
         0,
         0,
         &note.created_at,
+        &note.author_emojis,
+        note.author_avatar_url.as_deref(),
+        note.author_header_url.as_deref(),
     );
-    let status_json = status_json(
+    let mut status_json = status_json(
         &state,
         note.id,
         &note.username,
@@ -67,7 +70,12 @@ pub async fn post_favourite(
         false,
         note.parent_id,
         None,
+        &note.visibility,
+        note.summary.clone(),
+        note.is_sensitive == 1,
+        &note.emojis,
     );
+    add_tags(&state, &mut status_json).await;
 
     Json(status_json)
 }
@@ -110,8 +118,11 @@ pub async fn post_unfavourite(
         0,
         0,
         &note.created_at,
+        &note.author_emojis,
+        note.author_avatar_url.as_deref(),
+        note.author_header_url.as_deref(),
     );
-    let status_json = status_json(
+    let mut status_json = status_json(
         &state,
         note.id,
         &note.username,
@@ -128,7 +139,12 @@ pub async fn post_unfavourite(
         false,
         note.parent_id,
         None,
+        &note.visibility,
+        note.summary.clone(),
+        note.is_sensitive == 1,
+        &note.emojis,
     );
+    add_tags(&state, &mut status_json).await;
 
     Json(status_json)
 }
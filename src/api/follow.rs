@@ -25,7 +25,10 @@ pub async fn post_follow(
         Ok(_) => {
             // When local user
             if followee.is_local == 1 {
-                follow::accept(&state, user.id, followee.id).await;
+                // Locked users approve follow requests themselves
+                if followee.is_locked == 0 {
+                    follow::accept(&state, user.id, followee.id).await;
+                }
 
             // When remote user
             } else {
@@ -37,6 +40,7 @@ pub async fn post_follow(
                 "following": true,
                 "showing_reblogs": true,
                 "notifying": true,
+                "requested": followee.is_locked == 1,
             }))
         }
         Err(e) => Json(json!({"error": e})),
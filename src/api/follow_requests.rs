@@ -0,0 +1,100 @@
+use crate::api::auth::OAuthUser;
+use crate::api::following::{FollowingQuery, users_json};
+use crate::back::follow;
+use crate::back::init::AppState;
+use crate::back::queries;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde_json::{Value, json};
+
+pub async fn get(
+    State(state): State<AppState>,
+    Query(query): Query<FollowingQuery>,
+    user: OAuthUser,
+) -> Json<Value> {
+    // extract query parameters
+    let max_id = query.max_id.unwrap_or("".to_string());
+    let limit = query.limit.unwrap_or(40);
+    let limit = if limit > 80 { 80 } else { limit };
+
+    // Get follow requests
+    let requests = queries::follow::get_requests(&state, user.id, &max_id, limit).await;
+
+    Json(users_json(&state, requests))
+}
+
+pub async fn post_authorize(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    user: OAuthUser,
+) -> Json<Value> {
+    // Get follower
+    let Some(follower) = queries::user::get_by_username(&state, &username).await else {
+        return Json(json!({"error": "User not found"}));
+    };
+
+    // Approve
+    if let Err(e) = follow::approve(&state, follower.id, user.id).await {
+        return Json(json!({"error": e}));
+    }
+    if follower.is_local == 0 {
+        follow::deliver_accept(&state, follower.id, user.id).await;
+    }
+
+    let following = queries::follow::get(&state, user.id, follower.id).await;
+    Json(relationship_json(&follower.username, following, true))
+}
+
+pub async fn post_reject(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    user: OAuthUser,
+) -> Json<Value> {
+    // Get follower
+    let Some(follower) = queries::user::get_by_username(&state, &username).await else {
+        return Json(json!({"error": "User not found"}));
+    };
+
+    // Check if requested
+    match queries::follow::get(&state, follower.id, user.id).await {
+        Some(follow) if follow.pending == 1 => {}
+        _ => return Json(json!({"error": "Follow request not found"})),
+    }
+
+    // Deliver reject activity
+    if follower.is_local == 0 {
+        follow::deliver_reject(&state, follower.id, user.id).await;
+    }
+
+    // Reject
+    if let Err(e) = follow::reject(&state, follower.id, user.id).await {
+        return Json(json!({"error": e}));
+    }
+
+    let following = queries::follow::get(&state, user.id, follower.id).await;
+    Json(relationship_json(&follower.username, following, false))
+}
+
+fn relationship_json(
+    username: &str,
+    following: Option<queries::follow::FollowRecord>,
+    followed_by: bool,
+) -> Value {
+    json!({
+        "id": username,
+        "following": following.as_ref().is_some_and(|follow| follow.pending == 0),
+        "showing_reblogs": true,
+        "notifying": false,
+        "followed_by": followed_by,
+        "blocking": false,
+        "blocked_by": false,
+        "muting": false,
+        "muting_notifications": false,
+        "requested": following.is_some_and(|follow| follow.pending == 1),
+        "domain_blocking": false,
+        "endorsed": false
+    })
+}
@@ -22,6 +22,9 @@ pub fn users_json(state: &AppState, users: Vec<queries::follow::FollowUserRecord
                 0,
                 0,
                 "9999-01-01T00:00:00Z",
+                &user.emojis,
+                user.avatar_url.as_deref(),
+                user.header_url.as_deref(),
             )
         })
         .collect();
@@ -1,6 +1,8 @@
 use crate::VERSION;
 use crate::api::accounts::account_json;
 use crate::back::init::AppState;
+use crate::back::media;
+use crate::back::poll;
 
 use axum::{Json, extract::State};
 use serde_json::{Value, json};
@@ -16,7 +18,7 @@ pub async fn get_v1(State(state): State<AppState>) -> Json<Value> {
         "configuration": {
             "statuses": {
                 "max_characters": state.config.max_note_chars,
-                "max_media_attachments": 0,
+                "max_media_attachments": media::MAX_ATTACHMENTS,
             }
         },
     }))
@@ -33,6 +35,9 @@ pub async fn get_v2(State(state): State<AppState>) -> Json<Value> {
         0,
         0,
         "0000-01-01T00:00:00Z",
+        "",
+        None,
+        None,
     );
 
     Json(json!({
@@ -46,13 +51,21 @@ pub async fn get_v2(State(state): State<AppState>) -> Json<Value> {
         "configuration": {
             "statuses": {
                 "max_characters": state.config.max_note_chars,
-                "max_media_attachments": 0,
+                "max_media_attachments": media::MAX_ATTACHMENTS,
+            },
+            "media_attachments": {
+                "supported_mime_types": media::MEDIA_TYPES
+                    .iter()
+                    .map(|(media_type, _)| *media_type)
+                    .collect::<Vec<&str>>(),
+                "image_size_limit": state.config.max_media_bytes,
+                "video_size_limit": state.config.max_media_bytes,
             },
             "polls": {
-                "max_options": 0,
-                "max_characters_per_option": 0,
-                "min_expiration": 0,
-                "max_expiration": 0,
+                "max_options": poll::MAX_OPTIONS,
+                "max_characters_per_option": poll::MAX_OPTION_CHARS,
+                "min_expiration": poll::MIN_EXPIRATION,
+                "max_expiration": poll::MAX_EXPIRATION,
             },
         },
         "thumbnail": {},
@@ -0,0 +1,65 @@
+use crate::api::auth::OAuthUser;
+use crate::back::init::AppState;
+use crate::back::media;
+use crate::back::queries;
+use crate::back::queries::media::MediaRecord;
+use crate::back::utils;
+
+use axum::{
+    Json,
+    extract::{Multipart, State},
+};
+use serde_json::{Value, json};
+
+pub fn media_json(state: &AppState, record: &MediaRecord) -> Value {
+    let url = utils::media_url(&state.domain, &record.file_name);
+    json!({
+        "id": record.id.to_string(),
+        "type": media::attachment_type(&record.media_type),
+        "url": url,
+        "preview_url": url,
+        "remote_url": null,
+        "description": record.description,
+        "blurhash": null,
+        "meta": {},
+    })
+}
+
+pub async fn post(
+    State(state): State<AppState>,
+    user: OAuthUser,
+    mut multipart: Multipart,
+) -> Json<Value> {
+    let mut file: Option<(String, Vec<u8>)> = None;
+    let mut description: Option<String> = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        match field.name() {
+            Some("file") => {
+                let media_type = field.content_type().unwrap_or("").to_string();
+                match media::read_field(&state, field).await {
+                    Ok(data) => file = Some((media_type, data)),
+                    Err(e) => return Json(json!({"error": e})),
+                }
+            }
+            Some("description") => match media::read_text_field(field).await {
+                Ok(value) => description = Some(value),
+                Err(e) => return Json(json!({"error": e})),
+            },
+            _ => {}
+        }
+    }
+
+    let Some((media_type, data)) = file else {
+        return Json(json!({"error": "File is missing"}));
+    };
+
+    // Save
+    let res = media::add(&state, user.id, &media_type, &data, description).await;
+    let id = match res {
+        Ok(id) => id,
+        Err(e) => return Json(json!({"error": e})),
+    };
+
+    let record = queries::media::get_by_id(&state, id).await.unwrap();
+    Json(media_json(&state, &record))
+}
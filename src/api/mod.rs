@@ -1,13 +1,19 @@
 pub mod accounts;
 pub mod auth;
 pub mod context;
+pub mod conversations;
 pub mod favourite;
 pub mod follow;
+pub mod follow_requests;
 pub mod following;
 pub mod instance;
 pub mod lists;
+pub mod media;
 pub mod notifications;
 pub mod oauth;
+pub mod pin;
+pub mod polls;
+pub mod quotes;
 pub mod reblog;
 pub mod relationships;
 pub mod search;
@@ -1,6 +1,6 @@
 use crate::api::accounts::account_json;
 use crate::api::auth::OAuthUser;
-use crate::api::statuses::status_json;
+use crate::api::statuses::{add_tags, status_json};
 use crate::back::init::AppState;
 use crate::back::queries;
 use crate::back::utils;
@@ -38,21 +38,36 @@ pub async fn get(
         user.following_count,
         user.note_count,
         &user.updated_at,
+        &user.emojis,
+        user.avatar_url.as_deref(),
+        user.header_url.as_deref(),
     );
 
     // Get notifications
     let notifications = queries::notification::get_with_note(&state, user.id, &since, limit).await;
 
-    let notifications_json: Value = notifications
+    let note_ids = notifications
+        .iter()
+        .filter_map(|notif| notif.note_id)
+        .collect::<Vec<i64>>();
+    let note_attachments = queries::attachment::get_by_note_in(&state, &note_ids).await;
+
+    let mut notifications_json: Value = notifications
         .into_iter()
         .map(|notif| {
-            let attachments = utils::attachments_to_value(&state, &notif.attachments);
+            let attachments = note_attachments
+                .iter()
+                .filter(|attachment| Some(attachment.note_id) == notif.note_id)
+                .cloned()
+                .collect::<Vec<_>>();
+            let attachments = utils::attachments_to_value(&attachments);
             let event_type = match notif.event_type {
                 1 => "follow",
                 2 => "mention",
                 3 => "favourite",
                 4 => "reblog",
                 5 => "mention",
+                6 => "follow_request",
                 _ => "unknown",
             };
 
@@ -66,6 +81,9 @@ pub async fn get(
                 0,
                 0,
                 &notif.created_at,
+                "",
+                notif.avatar_url.as_deref(),
+                notif.header_url.as_deref(),
             );
             let status_json = if let Some(note_id) = notif.note_id {
                 Some(status_json(
@@ -85,6 +103,10 @@ pub async fn get(
                     false,
                     notif.parent_id,
                     None,
+                    notif.visibility.as_deref().unwrap_or("public"),
+                    notif.summary.clone(),
+                    notif.is_sensitive.unwrap_or(0) == 1,
+                    notif.emojis.as_deref().unwrap_or(""),
                 ))
             } else {
                 None
@@ -99,6 +121,13 @@ pub async fn get(
             })
         })
         .collect();
+    if let Some(notifications) = notifications_json.as_array_mut() {
+        for notification in notifications {
+            if notification["status"].is_object() {
+                add_tags(&state, &mut notification["status"]).await;
+            }
+        }
+    }
 
     Json(json!(notifications_json))
 }
@@ -0,0 +1,62 @@
+use crate::api::auth::OAuthUser;
+use crate::api::polls::add_poll;
+use crate::api::quotes::add_quote;
+use crate::api::timeline::timeline_json;
+use crate::back::init::AppState;
+use crate::back::pin;
+use crate::back::queries;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde_json::{Value, json};
+
+pub async fn post_pin(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: OAuthUser,
+) -> Json<Value> {
+    // Pin
+    let res = pin::pin(&state, user.id, id).await;
+    if let Err(e) = res {
+        return Json(json!({
+            "error": e
+        }));
+    };
+
+    // Deliver pin
+    pin::deliver_pin(&state, user.id, id).await;
+
+    Json(pinned_status_json(&state, id, user.id, true).await)
+}
+
+pub async fn post_unpin(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: OAuthUser,
+) -> Json<Value> {
+    // Unpin
+    let res = pin::unpin(&state, user.id, id).await;
+    if let Err(e) = res {
+        return Json(json!({
+            "error": e
+        }));
+    };
+
+    // Deliver unpin
+    pin::deliver_unpin(&state, user.id, id).await;
+
+    Json(pinned_status_json(&state, id, user.id, false).await)
+}
+
+async fn pinned_status_json(state: &AppState, id: i64, user_id: i64, is_pinned: bool) -> Value {
+    let note = queries::note::get_with_author_by_id(state, id)
+        .await
+        .unwrap();
+    let mut status_json = timeline_json(state, vec![note]).await[0].take();
+    status_json["pinned"] = json!(is_pinned);
+    add_poll(state, &mut status_json, Some(user_id)).await;
+    add_quote(state, &mut status_json).await;
+    status_json
+}
@@ -0,0 +1,128 @@
+use crate::api::auth::{MaybeOAuthUser, OAuthUser};
+use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::poll;
+use crate::back::poll::PollView;
+use crate::back::queries;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde_json::{Value, json};
+
+pub fn poll_json(poll: &PollView) -> Value {
+    let options: Vec<Value> = poll
+        .options
+        .iter()
+        .map(|option| {
+            json!({
+                "title": option.title,
+                "votes_count": option.votes_count,
+            })
+        })
+        .collect();
+
+    json!({
+        "id": poll.id.to_string(),
+        "expires_at": poll.expires_at,
+        "expired": poll.is_expired,
+        "multiple": poll.is_multiple,
+        "votes_count": poll.votes_count,
+        "voters_count": poll.voters_count,
+        "options": options,
+        "emojis": [],
+        "voted": !poll.own_votes.is_empty(),
+        "own_votes": poll.own_votes,
+    })
+}
+
+// Fill in poll for statuses and boosted statuses
+pub async fn add_polls(state: &AppState, statuses: &mut Value, viewer_id: Option<i64>) {
+    let Some(statuses) = statuses.as_array_mut() else {
+        return;
+    };
+    for status in statuses {
+        add_poll(state, status, viewer_id).await;
+    }
+}
+
+pub async fn add_poll(state: &AppState, status: &mut Value, viewer_id: Option<i64>) {
+    let status = if status["reblog"].is_object() {
+        &mut status["reblog"]
+    } else {
+        status
+    };
+    let Some(note_id) = status["id"].as_str().and_then(|id| id.parse::<i64>().ok()) else {
+        return;
+    };
+    if let Some(poll) = poll::get_view(state, note_id, viewer_id).await {
+        status["poll"] = poll_json(&poll);
+    }
+}
+
+async fn get_visible_poll(state: &AppState, id: i64, viewer_id: Option<i64>) -> Option<i64> {
+    let poll = queries::poll::get_by_id(state, id).await?;
+    let note = queries::note::get_by_id(state, poll.note_id).await?;
+    if !note::can_view(state, &note, viewer_id).await {
+        return None;
+    }
+    Some(note.id)
+}
+
+pub async fn get(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: MaybeOAuthUser,
+) -> Json<Value> {
+    let Some(note_id) = get_visible_poll(&state, id, user.id).await else {
+        return Json(json!({"error": "Poll not found"}));
+    };
+    let poll = poll::get_view(&state, note_id, user.id).await.unwrap();
+
+    Json(poll_json(&poll))
+}
+
+#[derive(serde::Deserialize)]
+pub struct VoteRequest {
+    // Clients send indexes as numbers or strings
+    pub choices: Vec<Value>,
+}
+
+pub async fn post_votes(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: OAuthUser,
+    Json(req): Json<VoteRequest>,
+) -> Json<Value> {
+    let Some(note_id) = get_visible_poll(&state, id, Some(user.id)).await else {
+        return Json(json!({"error": "Poll not found"}));
+    };
+    let choices = req
+        .choices
+        .iter()
+        .filter_map(|choice| {
+            choice
+                .as_i64()
+                .or(choice.as_str().and_then(|choice| choice.parse().ok()))
+        })
+        .collect::<Vec<i64>>();
+
+    // Vote
+    let res = poll::vote(&state, user.id, note_id, &choices).await;
+    if let Err(e) = res {
+        return Json(json!({"error": e}));
+    }
+
+    // Deliver vote
+    let note = queries::note::get_by_id(&state, note_id).await.unwrap();
+    let author = queries::user::get_by_id(&state, note.author_id).await;
+    if author.is_local == 0 {
+        poll::deliver_vote(&state, user.id, note_id, &choices).await;
+    }
+
+    let poll = poll::get_view(&state, note_id, Some(user.id))
+        .await
+        .unwrap();
+    Json(poll_json(&poll))
+}
@@ -0,0 +1,33 @@
+use crate::api::timeline::timeline_json;
+use crate::back::init::AppState;
+use crate::back::quote;
+
+use serde_json::{Value, json};
+
+// Fill in quote for statuses and boosted statuses
+pub async fn add_quotes(state: &AppState, statuses: &mut Value) {
+    let Some(statuses) = statuses.as_array_mut() else {
+        return;
+    };
+    for status in statuses {
+        add_quote(state, status).await;
+    }
+}
+
+pub async fn add_quote(state: &AppState, status: &mut Value) {
+    let status = if status["reblog"].is_object() {
+        &mut status["reblog"]
+    } else {
+        status
+    };
+    let Some(note_id) = status["id"].as_str().and_then(|id| id.parse::<i64>().ok()) else {
+        return;
+    };
+    if let Some(quoted) = quote::get(state, note_id).await {
+        let quoted_json = timeline_json(state, vec![quoted]).await;
+        status["quote"] = json!({
+            "state": "accepted",
+            "quoted_status": quoted_json[0],
+        });
+    }
+}
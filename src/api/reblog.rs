@@ -1,6 +1,6 @@
 use crate::api::accounts::account_json;
 use crate::api::auth::OAuthUser;
-use crate::api::statuses::status_json;
+use crate::api::statuses::{add_tags, status_json};
 use crate::back::boost;
 use crate::back::init::AppState;
 use crate::back::queries;
@@ -40,8 +40,11 @@ pub async fn post_reblog(
         0,
         0,
         &note.created_at,
+        &note.author_emojis,
+        note.author_avatar_url.as_deref(),
+        note.author_header_url.as_deref(),
     );
-    let status_json = status_json(
+    let mut status_json = status_json(
         &state,
         note.id,
         &note.username,
@@ -58,7 +61,12 @@ pub async fn post_reblog(
         true,
         note.parent_id,
         None,
+        &note.visibility,
+        note.summary.clone(),
+        note.is_sensitive == 1,
+        &note.emojis,
     );
+    add_tags(&state, &mut status_json).await;
 
     Json(status_json)
 }
@@ -92,8 +100,11 @@ pub async fn post_unreblog(
         0,
         0,
         &note.created_at,
+        &note.author_emojis,
+        note.author_avatar_url.as_deref(),
+        note.author_header_url.as_deref(),
     );
-    let status_json = status_json(
+    let mut status_json = status_json(
         &state,
         note.id,
         &note.username,
@@ -110,7 +121,12 @@ pub async fn post_unreblog(
         false,
         note.parent_id,
         None,
+        &note.visibility,
+        note.summary.clone(),
+        note.is_sensitive == 1,
+        &note.emojis,
     );
+    add_tags(&state, &mut status_json).await;
 
     Json(status_json)
 }
@@ -24,12 +24,14 @@ pub async fn get(
     // Get following, followers
     let following = queries::follow::get_following_in(&state, auth_user.id, &usernames).await;
     let followers = queries::follow::get_followers_in(&state, auth_user.id, &usernames).await;
+    let requested = queries::follow::get_requested_in(&state, auth_user.id, &usernames).await;
 
     // Check relationships
     let mut relationships: Vec<Value> = Vec::new();
     for username in usernames {
         let is_following = following.iter().any(|u| u.username == username);
         let is_followed_by = followers.iter().any(|u| u.username == username);
+        let is_requested = requested.iter().any(|u| u.username == username);
 
         relationships.push(json!({
             "id": username,
@@ -41,7 +43,7 @@ pub async fn get(
             "blocked_by": false,
             "muting": false,
             "muting_notifications": false,
-            "requested": false,
+            "requested": is_requested,
             "domain_blocking": false,
             "endorsed": false
         }));
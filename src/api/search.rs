@@ -1,7 +1,9 @@
 use crate::api::accounts::account_json;
 use crate::api::auth::OAuthUser;
-use crate::api::statuses::status_json;
+use crate::api::statuses::{add_tags, status_json};
+use crate::back::attachment;
 use crate::back::init::AppState;
+use crate::back::note;
 use crate::back::queries;
 use crate::back::search;
 use crate::back::utils;
@@ -20,7 +22,7 @@ pub struct SearchQuery {
 pub async fn get(
     State(state): State<AppState>,
     Query(query): Query<SearchQuery>,
-    _user: OAuthUser,
+    user: OAuthUser,
 ) -> Json<Value> {
     let Ok(path) = search::search(&state, &query.q).await else {
         return Json(json!({
@@ -45,6 +47,9 @@ pub async fn get(
             user.following_count,
             user.note_count,
             &user.updated_at,
+            &user.emojis,
+            user.avatar_url.as_deref(),
+            user.header_url.as_deref(),
         );
 
         Json(json!({
@@ -54,11 +59,18 @@ pub async fn get(
         }))
     } else {
         let note_id = parts[2].parse::<i64>().unwrap();
-        let Some(note) = queries::note::get_with_author_by_id(&state, note_id).await else {
+        let Some(note_record) = queries::note::get_by_id(&state, note_id).await else {
+            return Json(json!({"error": "Note not found"}));
+        };
+        if !note::can_view(&state, &note_record, Some(user.id)).await {
+            return Json(json!({"error": "Note not found"}));
+        }
+        let Some(mut note) = queries::note::get_with_author_by_id(&state, note_id).await else {
             return Json(json!({"error": "Note not found"}));
         };
 
-        let attachments = utils::attachments_to_value(&state, &note.attachments);
+        attachment::fill_one(&state, &mut note).await;
+        let attachments = utils::attachments_to_value(&note.attachments);
 
         let account_json = account_json(
             &state,
@@ -70,8 +82,11 @@ pub async fn get(
             0,
             0,
             &note.created_at,
+            &note.author_emojis,
+            note.author_avatar_url.as_deref(),
+            note.author_header_url.as_deref(),
         );
-        let status_json = status_json(
+        let mut status_json = status_json(
             &state,
             note.id,
             &note.username,
@@ -88,7 +103,12 @@ pub async fn get(
             false,
             note.parent_id,
             note.parent_author_username,
+            &note.visibility,
+            note.summary.clone(),
+            note.is_sensitive == 1,
+            &note.emojis,
         );
+        add_tags(&state, &mut status_json).await;
 
         Json(json!({
             "accounts": [],
@@ -1,8 +1,15 @@
 use crate::api::accounts::account_json;
-use crate::api::auth::OAuthUser;
+use crate::api::auth::{MaybeOAuthUser, OAuthUser};
+use crate::api::polls::add_poll;
+use crate::api::quotes::add_quote;
+use crate::back::attachment;
 use crate::back::init::AppState;
+use crate::back::media;
 use crate::back::note;
+use crate::back::poll;
 use crate::back::queries;
+use crate::back::queries::attachment::AttachmentRecord;
+use crate::back::quote;
 use crate::back::utils;
 
 use axum::{
@@ -11,6 +18,7 @@ use axum::{
 };
 use serde_json::{Value, json};
 
+#[allow(clippy::too_many_arguments)]
 pub fn status_json(
     state: &AppState,
     id: i64,
@@ -28,6 +36,10 @@ pub fn status_json(
     is_boosted: bool,
     parent_id: Option<i64>,
     parent_author_username: Option<String>,
+    visibility: &str,
+    summary: Option<String>,
+    is_sensitive: bool,
+    emojis: &str,
 ) -> Value {
     let parent_id_string = parent_id.map(|id| id.to_string());
     if let Some(boosted_id) = boosted_id {
@@ -45,14 +57,16 @@ pub fn status_json(
             "reblogged": false,
             "in_reply_to_id": null,
             "in_reply_to_account_id": null,
-            "visibility": "public",
-            "emojis": [],
+            "visibility": visibility,
+            "emojis": utils::emojis_to_value(emojis),
             "uri": utils::local_note_ap_url(&state.domain, boosted_id),
             "url": utils::note_url(&state.domain, author_username, boosted_id),
-            "sensitive": false,
-            "spoiler_text": "",
+            "sensitive": is_sensitive,
+            "spoiler_text": summary.as_deref().unwrap_or(""),
             "tags": [],
             "filtered": [],
+            "poll": null,
+            "quote": null,
             "reblog": null,
         });
         json!({
@@ -69,7 +83,7 @@ pub fn status_json(
             "reblogged": is_boosted,
             "in_reply_to_id": parent_id_string,
             "in_reply_to_account_id": parent_author_username,
-            "visibility": "public",
+            "visibility": visibility,
             "emojis": [],
             "uri": utils::local_note_ap_url(&state.domain, id),
             "url": utils::note_url(&state.domain, author_username, id),
@@ -77,6 +91,8 @@ pub fn status_json(
             "spoiler_text": "",
             "tags": [],
             "filtered": [],
+            "poll": null,
+            "quote": null,
             "reblog": reblog_json,
         })
     } else {
@@ -94,31 +110,78 @@ pub fn status_json(
             "reblogged": is_boosted,
             "in_reply_to_id": parent_id_string,
             "in_reply_to_account_id": parent_author_username,
-            "visibility": "public",
-            "emojis": [],
+            "visibility": visibility,
+            "emojis": utils::emojis_to_value(emojis),
             "uri": utils::local_note_ap_url(&state.domain, id),
             "url": utils::note_url(&state.domain, author_username, id),
-            "sensitive": false,
-            "spoiler_text": "",
+            "sensitive": is_sensitive,
+            "spoiler_text": summary.as_deref().unwrap_or(""),
             "tags": [],
             "filtered": [],
+            "poll": null,
+            "quote": null,
             "reblog": null,
         })
     }
 }
 
+// Fill in tags from stored hashtags for statuses and boosted statuses
+pub async fn add_tags_to_all(state: &AppState, statuses: &mut Value) {
+    let Some(statuses) = statuses.as_array_mut() else {
+        return;
+    };
+    for status in statuses {
+        add_tags(state, status).await;
+    }
+}
+
+pub async fn add_tags(state: &AppState, status: &mut Value) {
+    let status = if status["reblog"].is_object() {
+        &mut status["reblog"]
+    } else {
+        status
+    };
+    let Some(note_id) = status["id"].as_str().and_then(|id| id.parse::<i64>().ok()) else {
+        return;
+    };
+    let tags: Vec<Value> = queries::hashtag::get_by_note(state, note_id)
+        .await
+        .into_iter()
+        .map(|hashtag| {
+            json!({
+                "name": &hashtag.name,
+                "url": utils::hashtag_url(&state.domain, &hashtag.name),
+            })
+        })
+        .collect();
+    status["tags"] = json!(tags);
+}
+
 pub async fn get(
     State(state): State<AppState>,
     Path(id): Path<i64>,
     user: OAuthUser,
 ) -> Json<Value> {
-    let Some(note) = queries::note::get_with_author_by_id(&state, id).await else {
+    let Some(mut note) = queries::note::get_with_author_by_id(&state, id).await else {
+        return Json(json!({
+            "error": "Note not found"
+        }));
+    };
+
+    // Check privacy
+    let Some(note_record) = queries::note::get_by_id(&state, id).await else {
         return Json(json!({
             "error": "Note not found"
         }));
     };
+    if !note::can_view(&state, &note_record, Some(user.id)).await {
+        return Json(json!({
+            "error": "Note not found"
+        }));
+    }
 
-    let attachments = utils::attachments_to_value(&state, &note.attachments);
+    attachment::fill_one(&state, &mut note).await;
+    let attachments = utils::attachments_to_value(&note.attachments);
 
     // Check is_liked, is_boosted
     let is_liked = matches!(
@@ -141,8 +204,11 @@ pub async fn get(
         0,
         0,
         &note.created_at,
+        &note.author_emojis,
+        note.author_avatar_url.as_deref(),
+        note.author_header_url.as_deref(),
     );
-    let status_json = status_json(
+    let mut status_json = status_json(
         &state,
         note.id,
         &note.username,
@@ -159,15 +225,36 @@ pub async fn get(
         is_boosted,
         note.parent_id,
         note.parent_author_username,
+        &note.visibility,
+        note.summary.clone(),
+        note.is_sensitive == 1,
+        &note.emojis,
     );
+    add_tags(&state, &mut status_json).await;
+    add_poll(&state, &mut status_json, Some(user.id)).await;
+    add_quote(&state, &mut status_json).await;
 
     Json(status_json)
 }
 
+#[derive(serde::Deserialize)]
+pub struct PollRequest {
+    pub options: Vec<String>,
+    pub expires_in: i64,
+    pub multiple: Option<bool>,
+}
+
 #[derive(serde::Deserialize)]
 pub struct PostStatusRequest {
     pub status: String,
     pub in_reply_to_id: Option<String>,
+    pub visibility: Option<String>,
+    pub spoiler_text: Option<String>,
+    pub sensitive: Option<bool>,
+    pub poll: Option<PollRequest>,
+    pub media_ids: Option<Vec<String>>,
+    #[serde(alias = "quote_id")]
+    pub quoted_status_id: Option<String>,
 }
 
 pub async fn post(
@@ -185,6 +272,9 @@ pub async fn post(
     let id = utils::gen_unique_id();
     let ap_url = utils::local_note_ap_url(&state.domain, id);
     let created_at = utils::date_now();
+    let visibility = req.visibility.as_deref().unwrap_or("public");
+    let (summary, is_sensitive) =
+        note::normalize_summary(req.spoiler_text, req.sensitive.unwrap_or(false));
 
     // in_reply_to handling
     let parent_author_username = if let Some(parent_id) = in_reply_to_id {
@@ -193,12 +283,53 @@ pub async fn post(
                 "error": "Parent note not found"
             }));
         };
+        if !note::can_view(&state, &parent, Some(user.id)).await {
+            return Json(json!({
+                "error": "Parent note not found"
+            }));
+        }
         let parent_author = queries::user::get_by_id(&state, parent.author_id).await;
         Some(parent_author.username)
     } else {
         None
     };
 
+    // Check poll
+    if let Some(poll_req) = &req.poll
+        && let Err(e) = poll::validate(&poll_req.options, poll_req.expires_in)
+    {
+        return Json(json!({"error": e}));
+    }
+
+    // Check quote
+    let quote_id = req
+        .quoted_status_id
+        .as_ref()
+        .and_then(|id_str| id_str.parse::<i64>().ok());
+    if let Some(quote_id) = quote_id
+        && let Err(e) = quote::validate(&state, quote_id).await
+    {
+        return Json(json!({"error": e}));
+    }
+
+    if let Err(e) = note::check_length(&state, &req.status)
+        .and_then(|_| note::validate(&state, &req.status, visibility))
+    {
+        return Json(json!({"error": e}));
+    }
+
+    // Check media
+    let media_ids = req
+        .media_ids
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|id_str| id_str.parse::<i64>().ok())
+        .collect::<Vec<i64>>();
+    let media = match media::get_unattached(&state, user.id, &media_ids).await {
+        Ok(media) => media,
+        Err(e) => return Json(json!({"error": e})),
+    };
+
     // Create note
     let res = note::add(
         &state,
@@ -206,21 +337,40 @@ pub async fn post(
         &ap_url,
         user.id,
         &req.status,
-        None,
         in_reply_to_id,
         parent_author_username,
         &created_at,
-        1, // is_public
+        visibility,
+        summary.clone(),
+        is_sensitive == 1,
     )
     .await;
 
     if let Err(e) = res {
         println!("Error creating note: {}", e);
+        media::delete(&state, &media).await;
         return Json(json!({
             "error": "Something went wrong"
         }));
     }
 
+    media::attach(&state, id, &media).await;
+    if let Some(quote_id) = quote_id {
+        let _ = quote::add(&state, id, quote_id).await;
+    }
+
+    // Create poll
+    if let Some(poll_req) = &req.poll {
+        let _ = poll::add(
+            &state,
+            id,
+            &poll_req.options,
+            poll_req.expires_in,
+            poll_req.multiple.unwrap_or(false),
+        )
+        .await;
+    }
+
     // Deliver to followers and parent
     note::deliver_create(&state, id).await;
 
@@ -234,8 +384,11 @@ pub async fn post(
         user.following_count,
         user.note_count,
         &user.updated_at,
+        &user.emojis,
+        user.avatar_url.as_deref(),
+        user.header_url.as_deref(),
     );
-    let status_json = status_json(
+    let mut status_json = status_json(
         &state,
         id,
         &user.username,
@@ -245,14 +398,21 @@ pub async fn post(
         &req.status,
         &account_json,
         &created_at,
-        &vec![],
+        &utils::attachments_to_value(&queries::attachment::get_by_note(&state, id).await),
         0,
         0,
         false,
         false,
         in_reply_to_id,
         None,
+        visibility,
+        summary,
+        is_sensitive == 1,
+        "",
     );
+    add_tags(&state, &mut status_json).await;
+    add_poll(&state, &mut status_json, Some(user.id)).await;
+    add_quote(&state, &mut status_json).await;
 
     Json(status_json)
 }
@@ -289,6 +449,9 @@ pub async fn delete(
         0,
         0,
         &note.created_at,
+        &note.author_emojis,
+        note.author_avatar_url.as_deref(),
+        note.author_header_url.as_deref(),
     );
     let status_json = status_json(
         &state,
@@ -307,7 +470,209 @@ pub async fn delete(
         false,
         note.parent_id,
         note.parent_author_username,
+        &note.visibility,
+        note.summary.clone(),
+        note.is_sensitive == 1,
+        &note.emojis,
+    );
+
+    Json(status_json)
+}
+
+#[derive(serde::Deserialize)]
+pub struct EditStatusRequest {
+    pub status: String,
+    pub spoiler_text: Option<String>,
+    pub sensitive: Option<bool>,
+}
+
+pub async fn put(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: OAuthUser,
+    Json(req): Json<EditStatusRequest>,
+) -> Json<Value> {
+    // Get note
+    let Some(existing) = queries::note::get_by_id(&state, id).await else {
+        return Json(json!({"error": "Note not found"}));
+    };
+    if existing.author_id != user.id {
+        return Json(json!({"error": "Unauthorized"}));
+    }
+
+    // Update
+    let res = note::update(
+        &state,
+        id,
+        &req.status,
+        req.spoiler_text,
+        req.sensitive.unwrap_or(false),
+    )
+    .await;
+    let is_changed = match res {
+        Ok(is_changed) => is_changed,
+        Err(e) => return Json(json!({"error": e})),
+    };
+
+    // Deliver to followers and mentioned users
+    if is_changed {
+        note::deliver_update(&state, id).await;
+    }
+
+    let Some(mut note) = queries::note::get_with_author_by_id(&state, id).await else {
+        return Json(json!({"error": "Note not found"}));
+    };
+    let user = queries::user::get_by_id(&state, user.id).await;
+    attachment::fill_one(&state, &mut note).await;
+    let attachments = utils::attachments_to_value(&note.attachments);
+
+    let account_json = account_json(
+        &state,
+        &user.username,
+        &user.display_name,
+        &user.created_at,
+        &user.bio,
+        user.follower_count,
+        user.following_count,
+        user.note_count,
+        &user.updated_at,
+        &user.emojis,
+        user.avatar_url.as_deref(),
+        user.header_url.as_deref(),
+    );
+    let mut status_json = status_json(
+        &state,
+        note.id,
+        &note.username,
+        None,
+        None,
+        None,
+        &note.content,
+        &account_json,
+        &note.created_at,
+        &attachments,
+        note.like_count,
+        note.boost_count,
+        false,
+        false,
+        note.parent_id,
+        note.parent_author_username,
+        &note.visibility,
+        note.summary.clone(),
+        note.is_sensitive == 1,
+        &note.emojis,
     );
+    add_tags(&state, &mut status_json).await;
+    let edited_at = queries::note::get_by_id(&state, id)
+        .await
+        .and_then(|note| note.updated_at);
+    status_json["edited_at"] = json!(edited_at);
 
     Json(status_json)
 }
+
+pub async fn get_history(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: MaybeOAuthUser,
+) -> Json<Value> {
+    let Some(current) = queries::note::get_by_id(&state, id).await else {
+        return Json(json!({"error": "Note not found"}));
+    };
+    if !note::can_view(&state, &current, user.id).await {
+        return Json(json!({"error": "Note not found"}));
+    }
+    let Some(note) = queries::note::get_with_author_by_id(&state, id).await else {
+        return Json(json!({"error": "Note not found"}));
+    };
+
+    let account_json = account_json(
+        &state,
+        &note.username,
+        &note.display_name,
+        &note.created_at,
+        "",
+        0,
+        0,
+        0,
+        &note.created_at,
+        &note.author_emojis,
+        note.author_avatar_url.as_deref(),
+        note.author_header_url.as_deref(),
+    );
+
+    let attachments =
+        utils::attachments_to_value(&queries::attachment::get_by_note(&state, id).await);
+
+    // Previous versions, then current version
+    // Edits without a stored attachment list show current attachments
+    let mut versions: Vec<Value> = queries::note::get_edits(&state, id)
+        .await
+        .into_iter()
+        .map(|edit| {
+            let edit_attachments = edit
+                .attachments
+                .and_then(|json| serde_json::from_str::<Vec<AttachmentRecord>>(&json).ok())
+                .map(|records| utils::attachments_to_value(&records));
+            edit_json(
+                &edit.content,
+                edit.summary.as_deref(),
+                edit.is_sensitive == 1,
+                edit_attachments.as_ref().unwrap_or(&attachments),
+                &edit.created_at,
+                &account_json,
+            )
+        })
+        .collect();
+    let current_date = current.updated_at.unwrap_or(current.created_at);
+    versions.push(edit_json(
+        &note.content,
+        note.summary.as_deref(),
+        note.is_sensitive == 1,
+        &attachments,
+        &current_date,
+        &account_json,
+    ));
+
+    Json(json!(versions))
+}
+
+fn edit_json(
+    content: &str,
+    summary: Option<&str>,
+    is_sensitive: bool,
+    attachments: &Vec<Value>,
+    created_at: &str,
+    account_json: &Value,
+) -> Value {
+    json!({
+        "content": content,
+        "spoiler_text": summary.unwrap_or_default(),
+        "sensitive": is_sensitive,
+        "created_at": created_at,
+        "account": account_json,
+        "poll": null,
+        "media_attachments": attachments,
+        "emojis": [],
+    })
+}
+
+pub async fn get_source(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    user: OAuthUser,
+) -> Json<Value> {
+    let Some(note) = queries::note::get_by_id(&state, id).await else {
+        return Json(json!({"error": "Note not found"}));
+    };
+    if note.author_id != user.id {
+        return Json(json!({"error": "Unauthorized"}));
+    }
+
+    Json(json!({
+        "id": note.id.to_string(),
+        // Raw content
+        "text": utils::strip_content(&state, &note.content),
+        "spoiler_text": note.summary.unwrap_or_default(),
+    }))
+}
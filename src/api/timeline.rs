@@ -1,13 +1,16 @@
 use crate::api::accounts::account_json;
-use crate::api::auth::OAuthUser;
-use crate::api::statuses::status_json;
+use crate::api::auth::{MaybeOAuthUser, OAuthUser};
+use crate::api::polls::add_polls;
+use crate::api::quotes::add_quotes;
+use crate::api::statuses::{add_tags_to_all, status_json};
+use crate::back::attachment;
 use crate::back::init::AppState;
 use crate::back::queries;
 use crate::back::utils;
 
 use axum::{
     Json,
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::HeaderMap,
     response::IntoResponse,
 };
@@ -34,11 +37,16 @@ pub async fn extract_limit(limit: Option<i64>) -> i64 {
     if limit > 40 { 40 } else { limit }
 }
 
-pub fn timeline_json(state: &AppState, notes: Vec<queries::note::NoteWithAuthorRecord>) -> Value {
-    let notes_json: Value = notes
+pub async fn timeline_json(
+    state: &AppState,
+    mut notes: Vec<queries::note::NoteWithAuthorRecord>,
+) -> Value {
+    attachment::fill(state, &mut notes).await;
+
+    let mut notes_json: Value = notes
         .into_iter()
         .map(|note| {
-            let attachments = utils::attachments_to_value(state, &note.attachments);
+            let attachments = utils::attachments_to_value(&note.attachments);
 
             let author_json = account_json(
                 state,
@@ -50,6 +58,9 @@ pub fn timeline_json(state: &AppState, notes: Vec<queries::note::NoteWithAuthorR
                 0,
                 0,
                 &note.created_at,
+                &note.author_emojis,
+                note.author_avatar_url.as_deref(),
+                note.author_header_url.as_deref(),
             );
             let boosted_author_json = note.boosted_username.as_ref().map(|boosted_username| {
                 account_json(
@@ -62,6 +73,9 @@ pub fn timeline_json(state: &AppState, notes: Vec<queries::note::NoteWithAuthorR
                     0,
                     0,
                     "9999-01-01T00:00:00Z",
+                    "",
+                    None,
+                    None,
                 )
             });
 
@@ -82,9 +96,14 @@ pub fn timeline_json(state: &AppState, notes: Vec<queries::note::NoteWithAuthorR
                 false,
                 note.parent_id,
                 note.parent_author_username,
+                &note.visibility,
+                note.summary.clone(),
+                note.is_sensitive == 1,
+                &note.emojis,
             )
         })
         .collect();
+    add_tags_to_all(state, &mut notes_json).await;
 
     notes_json
 }
@@ -110,13 +129,16 @@ pub async fn get_home(
         headers.insert("Link", link.parse().unwrap());
     }
 
-    let notes_json = timeline_json(&state, notes);
+    let mut notes_json = timeline_json(&state, notes).await;
+    add_polls(&state, &mut notes_json, Some(user.id)).await;
+    add_quotes(&state, &mut notes_json).await;
     (headers, Json(notes_json))
 }
 
 pub async fn get_public(
     State(state): State<AppState>,
     Query(query): Query<TimelineQuery>,
+    user: MaybeOAuthUser,
 ) -> impl IntoResponse {
     let limit = extract_limit(query.limit).await;
     let is_local = query.local.unwrap_or(false);
@@ -145,6 +167,38 @@ pub async fn get_public(
         headers.insert("Link", link.parse().unwrap());
     }
 
-    let notes_json = timeline_json(&state, notes);
+    let mut notes_json = timeline_json(&state, notes).await;
+    add_polls(&state, &mut notes_json, user.id).await;
+    add_quotes(&state, &mut notes_json).await;
+    (headers, Json(notes_json))
+}
+
+pub async fn get_tag(
+    State(state): State<AppState>,
+    Path(hashtag): Path<String>,
+    Query(query): Query<TimelineQuery>,
+    user: MaybeOAuthUser,
+) -> impl IntoResponse {
+    let limit = extract_limit(query.limit).await;
+    let hashtag = hashtag.trim_start_matches('#').to_lowercase();
+
+    let notes = if let Some(max_id) = query.max_id {
+        let (until_date, until_id) = utils::extract_until_id(&state, Some(max_id)).await;
+        queries::timeline::get_tag(&state, &hashtag, &until_date, until_id, limit).await
+    } else {
+        let (since_date, since_id) = utils::extract_since_id(&state, query.since_id).await;
+        queries::timeline::get_tag_since(&state, &hashtag, &since_date, since_id, limit).await
+    };
+
+    let mut headers = HeaderMap::new();
+    if let (Some(first), Some(last)) = (notes.first(), notes.last()) {
+        let path = format!("/api/v1/timelines/tag/{}", hashtag);
+        let link = build_link_header(&state.domain, &path, last.id, first.id);
+        headers.insert("Link", link.parse().unwrap());
+    }
+
+    let mut notes_json = timeline_json(&state, notes).await;
+    add_polls(&state, &mut notes_json, user.id).await;
+    add_quotes(&state, &mut notes_json).await;
     (headers, Json(notes_json))
 }
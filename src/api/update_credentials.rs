@@ -1,8 +1,12 @@
+use crate::api::accounts::{account_json, add_fields};
 use crate::api::auth::OAuthUser;
 use crate::back::init::AppState;
+use crate::back::media;
 use crate::back::queries;
 use crate::back::user;
 
+use std::collections::BTreeMap;
+
 use axum::{
     Json,
     extract::{Multipart, State},
@@ -13,6 +17,12 @@ use serde_json::{Value, json};
 pub struct UpdateCredentialsRequest {
     pub display_name: Option<String>,
     pub note: Option<String>,
+    pub locked: Option<bool>,
+    // Media type and data
+    pub avatar: Option<(String, Vec<u8>)>,
+    pub header: Option<(String, Vec<u8>)>,
+    // fields_attributes[index][name] and fields_attributes[index][value]
+    pub fields: Option<BTreeMap<usize, (String, String)>>,
 }
 
 pub async fn patch(
@@ -24,14 +34,52 @@ pub async fn patch(
     let mut req = UpdateCredentialsRequest {
         display_name: None,
         note: None,
+        locked: None,
+        avatar: None,
+        header: None,
+        fields: None,
     };
-    while let Some(field) = multipart.next_field().await.unwrap() {
+    while let Ok(Some(field)) = multipart.next_field().await {
         let name = field.name().unwrap_or("").to_string();
-        let value = field.text().await.unwrap_or("".to_string());
+        if name == "avatar" || name == "header" {
+            let media_type = field.content_type().unwrap_or("").to_string();
+            let data = match media::read_field(&state, field).await {
+                Ok(data) => data,
+                Err(e) => return Json(json!({"error": e})),
+            };
+            if name == "avatar" {
+                req.avatar = Some((media_type, data));
+            } else {
+                req.header = Some((media_type, data));
+            }
+            continue;
+        }
+        let value = match media::read_text_field(field).await {
+            Ok(value) => value,
+            Err(e) => return Json(json!({"error": e})),
+        };
         match name.as_str() {
             "display_name" => req.display_name = Some(value),
             "note" => req.note = Some(value),
-            _ => {}
+            "locked" => req.locked = Some(value == "true" || value == "1"),
+            _ => {
+                let Some((index, key)) = name
+                    .strip_prefix("fields_attributes[")
+                    .and_then(|rest| rest.strip_suffix(']'))
+                    .and_then(|rest| rest.split_once("]["))
+                else {
+                    continue;
+                };
+                let Ok(index) = index.parse::<usize>() else {
+                    continue;
+                };
+                let field = req.fields.get_or_insert_default().entry(index).or_default();
+                match key {
+                    "name" => field.0 = value,
+                    "value" => field.1 = value,
+                    _ => {}
+                }
+            }
         }
     }
 
@@ -40,22 +88,47 @@ pub async fn patch(
     let display_name = req.display_name.unwrap_or(user.display_name);
     let bio = req.note.unwrap_or(user.bio);
 
-    user::update_profile(&state, user.id, &display_name, &bio).await;
+    let is_locked = req
+        .locked
+        .map(|locked| locked as i64)
+        .unwrap_or(user.is_locked);
 
-    Json(json!({
-        "id": &user.username,
-        "username": &user.username,
-        "display_name": &display_name,
-        "created_at": &user.created_at,
-        "note": &bio,
-        "url": &format!("https://{}/@{}", state.domain, &user.username),
-        "followers_count": user.follower_count,
-        "following_count": user.following_count,
-        "statuses_count": user.note_count,
-        "last_status_at": user.updated_at,
-        "source": {
-            "privacy": "public",
-            "sensitive": false,
+    if let Some((media_type, data)) = &req.avatar
+        && let Err(e) = user::update_avatar(&state, user.id, media_type, data).await
+    {
+        return Json(json!({"error": e}));
+    }
+    if let Some((media_type, data)) = &req.header
+        && let Err(e) = user::update_header(&state, user.id, media_type, data).await
+    {
+        return Json(json!({"error": e}));
+    }
+    if let Some(fields) = req.fields {
+        let fields = fields.into_values().collect::<Vec<(String, String)>>();
+        if let Err(e) = user::update_fields(&state, user.id, &fields).await {
+            return Json(json!({"error": e}));
         }
-    }))
+    }
+    user::update_profile(&state, user.id, &display_name, &bio).await;
+    queries::user::update_locked(&state, user.id, is_locked).await;
+
+    let user = queries::user::get_by_id(&state, user.id).await;
+    let mut account_json = account_json(
+        &state,
+        &user.username,
+        &user.display_name,
+        &user.created_at,
+        &user.bio,
+        user.follower_count,
+        user.following_count,
+        user.note_count,
+        &user.updated_at,
+        &user.emojis,
+        user.avatar_url.as_deref(),
+        user.header_url.as_deref(),
+    );
+    account_json["locked"] = json!(user.is_locked == 1);
+    add_fields(&state, &mut account_json, user.id).await;
+
+    Json(account_json)
 }
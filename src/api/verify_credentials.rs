@@ -1,15 +1,15 @@
-use crate::api::accounts::account_json;
+use crate::api::accounts::{account_json, add_fields};
 use crate::api::auth::OAuthUser;
 use crate::back::init::AppState;
 use crate::back::queries;
 
 use axum::{Json, extract::State};
-use serde_json::Value;
+use serde_json::{Value, json};
 
 pub async fn get(State(state): State<AppState>, user: OAuthUser) -> Json<Value> {
     let user = queries::user::get_by_id(&state, user.id).await;
 
-    let account_json = account_json(
+    let mut account_json = account_json(
         &state,
         &user.username,
         &user.display_name,
@@ -19,7 +19,12 @@ pub async fn get(State(state): State<AppState>, user: OAuthUser) -> Json<Value>
         user.following_count,
         user.note_count,
         &user.updated_at,
+        &user.emojis,
+        user.avatar_url.as_deref(),
+        user.header_url.as_deref(),
     );
+    account_json["locked"] = json!(user.is_locked == 1);
+    add_fields(&state, &mut account_json, user.id).await;
 
     Json(account_json)
 }
@@ -0,0 +1,237 @@
+use crate::back::init::AppState;
+use crate::back::media;
+use crate::back::queries;
+use crate::back::queries::note::NoteWithAuthorRecord;
+
+use serde_json::{Value, json};
+
+pub struct RemoteAttachment {
+    pub media_type: Option<String>,
+    pub url: String,
+    pub description: Option<String>,
+    pub blurhash: Option<String>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    // "x,y" from focalPoint
+    pub focal_point: Option<String>,
+}
+
+pub fn parse_from_json(note_json: &Value) -> Vec<RemoteAttachment> {
+    let attachments = match &note_json["attachment"] {
+        Value::Array(attachments) => attachments.iter().collect(),
+        Value::Object(_) => vec![&note_json["attachment"]],
+        _ => vec![],
+    };
+
+    attachments
+        .into_iter()
+        .filter_map(|attachment| {
+            // url is str, Link object or array of Link objects
+            let url = match &attachment["url"] {
+                Value::String(url) => Some(url.as_str()),
+                Value::Array(links) => links.first().and_then(|link| link["href"].as_str()),
+                link => link["href"].as_str(),
+            }?;
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return None;
+            }
+
+            let focal_point = attachment["focalPoint"].as_array().and_then(|point| {
+                let x = point.first()?.as_f64()?;
+                let y = point.get(1)?.as_f64()?;
+                Some(format!("{},{}", x, y))
+            });
+
+            Some(RemoteAttachment {
+                media_type: attachment["mediaType"].as_str().map(|s| s.to_string()),
+                url: url.to_string(),
+                description: attachment["name"]
+                    .as_str()
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty()),
+                blurhash: attachment["blurhash"].as_str().map(|s| s.to_string()),
+                width: attachment["width"].as_i64(),
+                height: attachment["height"].as_i64(),
+                focal_point,
+            })
+        })
+        .collect()
+}
+
+// Attachments column from before the attachments table, one "type: <a href=url>url</a>" per line
+pub fn parse_legacy(legacy: &str) -> Vec<RemoteAttachment> {
+    legacy
+        .split("<br>")
+        .filter_map(|line| {
+            let (kind, url) = if let Some((kind, rest)) = line.split_once("<a ") {
+                let (_, rest) = rest.split_once("href=\"")?;
+                (kind, rest.split('"').next()?)
+            } else {
+                line.split_once(": ").unwrap_or(("", line))
+            };
+            let url = url.trim().replace("&amp;", "&");
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return None;
+            }
+
+            // Exact type from the file extension, else the coarse type
+            let kind = kind.trim().trim_end_matches(':').trim();
+            let extension = url.rsplit_once('.').map(|(_, extension)| extension);
+            let media_type = media::MEDIA_TYPES
+                .iter()
+                .find(|(_, ext)| Some(*ext) == extension)
+                .map(|(media_type, _)| media_type.to_string())
+                .or(["image", "gifv", "video", "audio"]
+                    .contains(&kind)
+                    .then(|| format!("{}/*", kind)));
+
+            Some(RemoteAttachment {
+                media_type,
+                url,
+                description: None,
+                blurhash: None,
+                width: None,
+                height: None,
+                focal_point: None,
+            })
+        })
+        .collect()
+}
+
+// Replace attachments of a remote note
+pub async fn add_remote(state: &AppState, note_id: i64, attachments: &[RemoteAttachment]) {
+    queries::attachment::delete_by_note(state, note_id).await;
+    for attachment in attachments {
+        queries::attachment::create(
+            state,
+            note_id,
+            attachment.media_type.as_deref(),
+            &attachment.url,
+            attachment.description.as_deref(),
+            attachment.blurhash.as_deref(),
+            attachment.width,
+            attachment.height,
+            attachment.focal_point.as_deref(),
+        )
+        .await;
+    }
+}
+
+// Boosts show attachments of the boosted note
+pub async fn fill(state: &AppState, notes: &mut [NoteWithAuthorRecord]) {
+    let note_ids = notes
+        .iter()
+        .map(|note| note.boosted_id.unwrap_or(note.id))
+        .collect::<Vec<i64>>();
+    let attachments = queries::attachment::get_by_note_in(state, &note_ids).await;
+    for note in notes {
+        let note_id = note.boosted_id.unwrap_or(note.id);
+        note.attachments = attachments
+            .iter()
+            .filter(|attachment| attachment.note_id == note_id)
+            .cloned()
+            .collect();
+    }
+}
+
+pub async fn fill_one(state: &AppState, note: &mut NoteWithAuthorRecord) {
+    note.attachments =
+        queries::attachment::get_by_note(state, note.boosted_id.unwrap_or(note.id)).await;
+}
+
+pub async fn document_objects(state: &AppState, note_id: i64) -> Vec<Value> {
+    queries::attachment::get_by_note(state, note_id)
+        .await
+        .into_iter()
+        .map(|attachment| {
+            let mut document = json!({
+                "type": "Document",
+                "mediaType": attachment.media_type,
+                "url": attachment.url,
+                "name": attachment.description,
+            });
+            if let Some(blurhash) = attachment.blurhash {
+                document["blurhash"] = json!(blurhash);
+            }
+            if let (Some(width), Some(height)) = (attachment.width, attachment.height) {
+                document["width"] = json!(width);
+                document["height"] = json!(height);
+            }
+            if let Some((x, y)) = attachment
+                .focal_point
+                .as_deref()
+                .and_then(|focal_point| focal_point.split_once(','))
+                && let (Ok(x), Ok(y)) = (x.parse::<f64>(), y.parse::<f64>())
+            {
+                document["focalPoint"] = json!([x, y]);
+            }
+            document
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_from_json_reads_document_fields() {
+        let note_json = json!({
+            "attachment": [
+                {
+                    "type": "Document",
+                    "mediaType": "image/png",
+                    "url": "https://r.example/a.png",
+                    "name": " a cat ",
+                    "blurhash": "LEHV6nWB2yk8",
+                    "width": 640,
+                    "height": 480,
+                    "focalPoint": [0.5, -0.25],
+                },
+                {"type": "Document", "url": [{"type": "Link", "href": "https://r.example/b.mp4"}]},
+                {"type": "Document", "url": {"type": "Link", "href": "https://r.example/c.mp3"}},
+                {"type": "Document", "url": "data:image/png;base64,AAAA"},
+            ],
+        });
+        let attachments = parse_from_json(&note_json);
+        assert_eq!(attachments.len(), 3);
+
+        let first = &attachments[0];
+        assert_eq!(first.media_type.as_deref(), Some("image/png"));
+        assert_eq!(first.url, "https://r.example/a.png");
+        assert_eq!(first.description.as_deref(), Some("a cat"));
+        assert_eq!(first.blurhash.as_deref(), Some("LEHV6nWB2yk8"));
+        assert_eq!((first.width, first.height), (Some(640), Some(480)));
+        assert_eq!(first.focal_point.as_deref(), Some("0.5,-0.25"));
+
+        assert_eq!(attachments[1].url, "https://r.example/b.mp4");
+        assert_eq!(attachments[1].media_type, None);
+        assert_eq!(attachments[2].url, "https://r.example/c.mp3");
+    }
+
+    #[test]
+    fn parse_from_json_accepts_single_attachment() {
+        let note_json =
+            json!({"attachment": {"type": "Document", "url": "https://r.example/a.png"}});
+        assert_eq!(parse_from_json(&note_json).len(), 1);
+        assert!(parse_from_json(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn parse_legacy_reads_type_and_url_lines() {
+        let legacy = concat!(
+            r#"image: <a class="link-in-note" href="https://r.example/a.png">https://r.example/a.png</a><br>"#,
+            r#"video: <a class="link-in-note" href="https://r.example/v?a=1&amp;b=2">x</a><br>"#,
+            "unknown: https://r.example/file<br>",
+            "image: javascript:alert(1)<br>",
+        );
+        let attachments = parse_legacy(legacy);
+        assert_eq!(attachments.len(), 3);
+        assert_eq!(attachments[0].url, "https://r.example/a.png");
+        assert_eq!(attachments[0].media_type.as_deref(), Some("image/png"));
+        assert_eq!(attachments[1].url, "https://r.example/v?a=1&b=2");
+        assert_eq!(attachments[1].media_type.as_deref(), Some("video/*"));
+        assert_eq!(attachments[2].url, "https://r.example/file");
+        assert_eq!(attachments[2].media_type, None);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::back::init::AppState;
+use crate::back::note;
 use crate::back::notification;
 use crate::back::queries;
 use crate::back::utils;
@@ -16,6 +17,12 @@ pub async fn boost(state: &AppState, user_id: i64, note_id: i64) -> Result<(), S
     let Some(note) = queries::note::get_by_id(state, note_id).await else {
         return Err("Note not found".to_string());
     };
+    if !note::can_view(state, &note, Some(user_id)).await {
+        return Err("Note not found".to_string());
+    }
+    if note.visibility != "public" && note.visibility != "unlisted" {
+        return Err("Cannot boost this note".to_string());
+    }
 
     // Get author
     let author = queries::user::get_by_id(state, note.author_id).await;
@@ -33,10 +40,13 @@ pub async fn boost(state: &AppState, user_id: i64, note_id: i64) -> Result<(), S
         &author.username,
         &note.created_at,
         &note.content,
-        note.attachments,
         note.parent_id,
         note.parent_author_username,
         &date_now,
+        &note.visibility,
+        note.summary,
+        note.is_sensitive,
+        &note.emojis,
     )
     .await;
 
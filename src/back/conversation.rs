@@ -0,0 +1,56 @@
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::back::queries::user::UserRecord;
+use crate::back::utils;
+
+// Group a direct note with earlier ones between the same participants
+pub async fn add_note(state: &AppState, note_id: i64) {
+    let Some(note) = queries::note::get_by_id(state, note_id).await else {
+        return;
+    };
+    if note.visibility != "direct" {
+        return;
+    }
+
+    // Participants are the author and mentioned users
+    let mut user_ids = vec![note.author_id];
+    for mention in queries::mention::get_by_note(state, note.id).await {
+        user_ids.push(mention.user_id);
+    }
+    user_ids.sort();
+    user_ids.dedup();
+    let participants = user_ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+
+    // Get or create conversation
+    queries::conversation::create(state, &participants, &utils::date_now()).await;
+    let conversation = queries::conversation::get_by_participants(state, &participants)
+        .await
+        .unwrap();
+    queries::note::update_conversation_id(state, note.id, conversation.id).await;
+
+    // Mark as unread for local participants other than the author
+    for user in queries::user::get_by_id_in(state, &user_ids).await {
+        if user.is_local == 1 {
+            let is_unread = (user.id != note.author_id) as i64;
+            queries::conversation::upsert_member(state, conversation.id, user.id, is_unread).await;
+        }
+    }
+}
+
+// Participants other than the user
+pub async fn get_participants(
+    state: &AppState,
+    participants: &str,
+    user_id: i64,
+) -> Vec<UserRecord> {
+    let ids = participants
+        .split(',')
+        .filter_map(|id| id.parse::<i64>().ok())
+        .filter(|id| *id != user_id)
+        .collect::<Vec<i64>>();
+    queries::user::get_by_id_in(state, &ids).await
+}
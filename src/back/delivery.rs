@@ -0,0 +1,125 @@
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::back::queries::delivery::DeliveryRecord;
+use crate::back::utils;
+
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+const BATCH_SIZE: i64 = 100;
+const POLL_INTERVAL: u64 = 30; // seconds
+// In-flight deliveries are hidden from the queue for this long
+const LEASE_SECONDS: i64 = 600;
+// Retry after 1m, 2m, 4m, ... up to 12h, and give up after 12 attempts (about a day)
+const RETRY_BASE_SECONDS: i64 = 60;
+const RETRY_MAX_SECONDS: i64 = 12 * 3600;
+const MAX_ATTEMPTS: i64 = 12;
+
+pub async fn worker(state: AppState) {
+    loop {
+        let due = queries::delivery::get_due(&state, &utils::date_now(), BATCH_SIZE).await;
+
+        // Sleep until new delivery is queued or next poll
+        if due.is_empty() {
+            let _ = tokio::time::timeout(
+                Duration::from_secs(POLL_INTERVAL),
+                state.deliver_notify.notified(),
+            )
+            .await;
+            continue;
+        }
+
+        for delivery in due {
+            let permit = state.deliver_queue.clone().acquire_owned().await.unwrap();
+
+            // Lease so that the next poll does not pick it up again
+            let lease_until = utils::date_plus_seconds(LEASE_SECONDS);
+            queries::delivery::reschedule(&state, delivery.id, delivery.attempts, &lease_until)
+                .await;
+
+            let state = state.clone();
+            tokio::spawn(async move {
+                attempt(&state, delivery).await;
+                drop(permit);
+            });
+        }
+    }
+}
+
+async fn attempt(state: &AppState, delivery: DeliveryRecord) {
+    // Sender can no longer sign
+    let Some(private_key) = delivery.private_key else {
+        queries::delivery::delete(state, delivery.id).await;
+        return;
+    };
+
+    let res = utils::signed_post(
+        state,
+        &delivery.sender_ap_url,
+        &private_key,
+        &delivery.inbox_url,
+        &delivery.body,
+    )
+    .await;
+
+    // Retry on network errors, 5xx, 408 and 429
+    let retryable = match res {
+        Ok(res) if res.status().is_success() => {
+            queries::delivery::delete(state, delivery.id).await;
+            return;
+        }
+        Ok(res) => {
+            let status = res.status();
+            println!("Delivery to {} failed: {}", delivery.inbox_url, status);
+            !status.is_client_error() || status.as_u16() == 408 || status.as_u16() == 429
+        }
+        Err(e) => {
+            println!("Delivery to {} failed: {}", delivery.inbox_url, e);
+            true
+        }
+    };
+
+    let attempts = delivery.attempts + 1;
+    if !retryable || attempts >= MAX_ATTEMPTS {
+        println!(
+            "Giving up delivery to {} after {} attempts",
+            delivery.inbox_url, attempts
+        );
+        queries::delivery::delete(state, delivery.id).await;
+        return;
+    }
+
+    let delay = (RETRY_BASE_SECONDS << (attempts - 1).min(20)).min(RETRY_MAX_SECONDS);
+    let next_attempt_at = utils::date_plus_seconds(delay);
+    queries::delivery::reschedule(state, delivery.id, attempts, &next_attempt_at).await;
+}
+
+// Send without queueing, for activities that must go out before the sender is deleted
+pub async fn send_now(
+    state: &AppState,
+    sender_ap_url: &str,
+    private_key: &str,
+    inboxes: Vec<String>,
+    body: &str,
+) {
+    let mut tasks = JoinSet::new();
+    for inbox in inboxes {
+        let permit = state.deliver_queue.clone().acquire_owned().await.unwrap();
+        let state = state.clone();
+        let sender_ap_url = sender_ap_url.to_string();
+        let private_key = private_key.to_string();
+        let body = body.to_string();
+        tasks.spawn(async move {
+            let res = utils::signed_post(&state, &sender_ap_url, &private_key, &inbox, &body).await;
+            match res {
+                Ok(res) if !res.status().is_success() => {
+                    println!("Delivery to {} failed: {}", inbox, res.status());
+                }
+                Err(e) => println!("Delivery to {} failed: {}", inbox, e),
+                _ => {}
+            }
+            drop(permit);
+        });
+    }
+    tasks.join_all().await;
+}
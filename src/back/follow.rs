@@ -21,14 +21,13 @@ pub async fn follow(state: &AppState, follower_id: i64, followee_id: i64) -> Res
     queries::follow::create(state, follower_id, followee_id).await;
 
     // Add notification
-    notification::add(
-        state,
-        notification::EventType::Follow,
-        follower_id,
-        followee_id,
-        None,
-    )
-    .await;
+    let followee = queries::user::get_by_id(state, followee_id).await;
+    let event_type = if followee.is_locked == 1 {
+        notification::EventType::FollowRequest
+    } else {
+        notification::EventType::Follow
+    };
+    notification::add(state, event_type, follower_id, followee_id, None).await;
 
     Ok(())
 }
@@ -47,15 +46,7 @@ pub async fn deliver_follow(state: &AppState, follower_id: i64, followee_id: i64
     });
     let json_body = follow_activity.to_string();
 
-    let private_key = follower.private_key.unwrap();
-    utils::signed_deliver(
-        state,
-        &follower.ap_url,
-        &private_key,
-        &followee.inbox_url,
-        &json_body,
-    )
-    .await;
+    utils::signed_deliver(state, follower.id, &followee.inbox_url, &json_body).await;
 }
 
 pub async fn accept(state: &AppState, follower_id: i64, followee_id: i64) {
@@ -66,6 +57,78 @@ pub async fn accept(state: &AppState, follower_id: i64, followee_id: i64) {
     queries::user::increment_follower_count(state, followee_id).await;
 }
 
+pub async fn approve(state: &AppState, follower_id: i64, followee_id: i64) -> Result<(), String> {
+    // Check if requested
+    let Some(follow) = queries::follow::get(state, follower_id, followee_id).await else {
+        return Err("Follow request not found".to_string());
+    };
+    if follow.pending == 0 {
+        return Err("Already following".to_string());
+    }
+
+    accept(state, follower_id, followee_id).await;
+    Ok(())
+}
+
+pub async fn deliver_accept(state: &AppState, follower_id: i64, followee_id: i64) {
+    deliver_response(state, "Accept", follower_id, followee_id).await;
+}
+
+pub async fn reject(state: &AppState, follower_id: i64, followee_id: i64) -> Result<(), String> {
+    // Check if requested
+    let Some(follow) = queries::follow::get(state, follower_id, followee_id).await else {
+        return Err("Follow request not found".to_string());
+    };
+    if follow.pending == 0 {
+        return Err("Already following".to_string());
+    }
+
+    queries::follow::delete(state, follower_id, followee_id).await;
+    Ok(())
+}
+
+pub async fn deliver_reject(state: &AppState, follower_id: i64, followee_id: i64) {
+    deliver_response(state, "Reject", follower_id, followee_id).await;
+}
+
+// Accept or Reject the stored Follow activity of a remote follower
+async fn deliver_response(
+    state: &AppState,
+    response_type: &str,
+    follower_id: i64,
+    followee_id: i64,
+) {
+    let follower = queries::user::get_by_id(state, follower_id).await;
+    let followee = queries::user::get_by_id(state, followee_id).await;
+    let follow = queries::follow::get(state, follower_id, followee_id).await;
+
+    let mut follow_object = json!({
+        "type": "Follow",
+        "actor": follower.ap_url,
+        "object": followee.ap_url,
+    });
+    if let Some(follow_ap_url) = follow.and_then(|follow| follow.ap_url) {
+        follow_object["id"] = json!(follow_ap_url);
+    }
+
+    let response_id = format!(
+        "{}#{}-{}",
+        followee.ap_url,
+        response_type.to_lowercase(),
+        utils::gen_unique_id()
+    );
+    let response_activity = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": response_id,
+        "type": response_type,
+        "actor": followee.ap_url,
+        "object": follow_object,
+    });
+    let json_body = response_activity.to_string();
+
+    utils::signed_deliver(state, followee.id, &follower.inbox_url, &json_body).await;
+}
+
 pub async fn unfollow(state: &AppState, follower_id: i64, followee_id: i64) -> Result<(), String> {
     // Check if following
     let Some(follow) = queries::follow::get(state, follower_id, followee_id).await else {
@@ -101,13 +164,30 @@ pub async fn deliver_unfollow(state: &AppState, follower_id: i64, followee_id: i
     });
     let json_body = unfollow_activity.to_string();
 
-    let private_key = follower.private_key.unwrap();
-    utils::signed_deliver(
-        state,
-        &follower.ap_url,
-        &private_key,
-        &followee.inbox_url,
-        &json_body,
-    )
-    .await;
+    utils::signed_deliver(state, follower.id, &followee.inbox_url, &json_body).await;
+}
+
+pub async fn move_followers(state: &AppState, old_id: i64, new_id: i64) {
+    let old = queries::user::get_by_id(state, old_id).await;
+    let new = queries::user::get_by_id(state, new_id).await;
+
+    // Re-point local followers from the old account to the new one
+    let followers = queries::follow::get_local_follower_ids(state, old_id).await;
+    for follower in followers {
+        let follower_id = follower.follower_id;
+
+        if unfollow(state, follower_id, old_id).await.is_ok() && old.is_local == 0 {
+            deliver_unfollow(state, follower_id, old_id).await;
+        }
+
+        if follow(state, follower_id, new_id).await.is_ok() {
+            if new.is_local == 1 {
+                if new.is_locked == 0 {
+                    accept(state, follower_id, new_id).await;
+                }
+            } else {
+                deliver_follow(state, follower_id, new_id).await;
+            }
+        }
+    }
 }
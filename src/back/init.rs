@@ -4,6 +4,8 @@ compile_error!("Either 'sqlite' or 'postgres' feature must be enabled");
 compile_error!("Only one of 'sqlite' or 'postgres' features can be enabled");
 
 use crate::VERSION;
+#[cfg(feature = "web")]
+use crate::back::utils;
 
 use regex::Regex;
 use reqwest::Client;
@@ -14,7 +16,7 @@ use std::io::{BufRead, BufReader};
 use std::sync::Arc;
 #[cfg(feature = "web")]
 use tera::Tera;
-use tokio::sync::Semaphore;
+use tokio::sync::{Notify, Semaphore};
 
 pub type AppState = Arc<AppStateInner>;
 
@@ -81,6 +83,7 @@ pub struct AppStateInner {
     #[cfg(feature = "web")]
     pub tera: Tera,
     pub deliver_queue: Arc<Semaphore>,
+    pub deliver_notify: Arc<Notify>,
     pub hash_queue: Arc<Semaphore>,
     pub sign_queue: Arc<Semaphore>,
     pub http_client: Client,
@@ -97,6 +100,7 @@ pub struct Re {
     pub br: Regex,
     pub tag: Regex,
     pub link: Regex,
+    pub hashtag: Regex,
 }
 
 #[derive(Clone)]
@@ -111,6 +115,9 @@ pub struct Metadata {
 pub struct Config {
     pub max_note_chars: usize,
     pub token_ttl_days: i64,
+    pub hide_follow_lists: bool,
+    pub media_dir: String,
+    pub max_media_bytes: usize,
 }
 
 #[cfg(feature = "web")]
@@ -150,7 +157,9 @@ pub async fn create_app_state() -> AppState {
     let db_pool = create_db_pool(&conf).await;
 
     #[cfg(feature = "web")]
-    let tera = Tera::new(&format!("{}/templates/**/*", web_dir())).unwrap();
+    let mut tera = Tera::new(&format!("{}/templates/**/*", web_dir())).unwrap();
+    #[cfg(feature = "web")]
+    tera.register_filter("emojify", emojify_filter);
 
     let domain = conf.get("domain").expect("domain must be set").to_string();
 
@@ -200,6 +209,24 @@ pub async fn create_app_state() -> AppState {
         .parse::<i64>()
         .expect("token_ttl_days must be an integer");
 
+    let hide_follow_lists = conf
+        .get("hide_follow_lists")
+        .unwrap_or(&"false".to_string())
+        .parse::<bool>()
+        .expect("hide_follow_lists must be a boolean");
+
+    let media_dir = conf
+        .get("media_dir")
+        .unwrap_or(&"./media".to_string())
+        .to_string();
+    std::fs::create_dir_all(&media_dir).expect("Failed to create media_dir");
+
+    let max_media_mb = conf
+        .get("max_media_mb")
+        .unwrap_or(&"10".to_string())
+        .parse::<usize>()
+        .expect("max_media_mb must be an integer");
+
     let deliver_queue_size = conf
         .get("deliver_queue_size")
         .expect("deliver_queue_size must be set")
@@ -243,6 +270,7 @@ pub async fn create_app_state() -> AppState {
         #[cfg(feature = "web")]
         tera,
         deliver_queue: Arc::new(Semaphore::new(deliver_queue_size)),
+        deliver_notify: Arc::new(Notify::new()),
         hash_queue: Arc::new(Semaphore::new(hash_queue_size)),
         sign_queue: Arc::new(Semaphore::new(sign_queue_size)),
         http_client,
@@ -251,6 +279,7 @@ pub async fn create_app_state() -> AppState {
             br: Regex::new(r"(?i)<br\s*/?>").unwrap(),
             tag: Regex::new(r"<[^>]+>").unwrap(),
             link: Regex::new(r"(https?:&#x2F;&#x2F;[^\s<>()]+)").unwrap(),
+            hashtag: Regex::new(r"(^|\s|<br>)#(\w+)").unwrap(),
         },
         metadata: Metadata {
             instance_name,
@@ -261,6 +290,9 @@ pub async fn create_app_state() -> AppState {
         config: Config {
             max_note_chars,
             token_ttl_days,
+            hide_follow_lists,
+            media_dir,
+            max_media_bytes: max_media_mb * 1024 * 1024,
         },
         #[cfg(feature = "web")]
         web_config: WebConfig {
@@ -317,3 +349,17 @@ impl Resolve for SsrfSafeResolver {
         })
     }
 }
+
+// {{ content | emojify(emojis=note.emojis) | safe }}
+#[cfg(feature = "web")]
+fn emojify_filter(
+    value: &tera::Value,
+    args: &HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    let content = tera::try_get_value!("emojify", "value", String, value);
+    let emojis = args
+        .get("emojis")
+        .and_then(|emojis| emojis.as_str())
+        .unwrap_or("");
+    Ok(tera::Value::String(utils::emojify(&content, emojis)))
+}
@@ -1,4 +1,5 @@
 use crate::back::init::AppState;
+use crate::back::note;
 use crate::back::notification;
 use crate::back::queries;
 use crate::back::utils;
@@ -16,6 +17,9 @@ pub async fn like(state: &AppState, user_id: i64, note_id: i64) -> Result<(), St
     let Some(note) = queries::note::get_by_id(state, note_id).await else {
         return Err("Note not found".to_string());
     };
+    if !note::can_view(state, &note, Some(user_id)).await {
+        return Err("Note not found".to_string());
+    }
 
     // Like
     queries::like::create(state, user_id, note_id).await;
@@ -51,15 +55,7 @@ pub async fn deliver_like(state: &AppState, user_id: i64, note_id: i64) {
     });
     let json_body = like_activity.to_string();
 
-    let private_key = user.private_key.unwrap();
-    utils::signed_deliver(
-        state,
-        &user.ap_url,
-        &private_key,
-        &author.inbox_url,
-        &json_body,
-    )
-    .await;
+    utils::signed_deliver(state, user.id, &author.inbox_url, &json_body).await;
 }
 
 pub async fn unlike(state: &AppState, user_id: i64, note_id: i64) -> Result<(), String> {
@@ -96,13 +92,5 @@ pub async fn deliver_unlilke(state: &AppState, user_id: i64, note_id: i64) {
     });
     let json_body = unlike_activity.to_string();
 
-    let private_key = user.private_key.unwrap();
-    utils::signed_deliver(
-        state,
-        &user.ap_url,
-        &private_key,
-        &author.inbox_url,
-        &json_body,
-    )
-    .await;
+    utils::signed_deliver(state, user.id, &author.inbox_url, &json_body).await;
 }
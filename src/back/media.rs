@@ -0,0 +1,219 @@
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::back::queries::media::MediaRecord;
+use crate::back::utils;
+
+use std::path::Path;
+
+pub const MAX_ATTACHMENTS: usize = 4;
+#[cfg(any(feature = "web", feature = "api"))]
+pub const MAX_TEXT_FIELD_BYTES: usize = 64 * 1024;
+// Room for text fields and part headers in multipart bodies
+#[cfg(any(feature = "web", feature = "api"))]
+const FORM_SLACK_BYTES: usize = 1024 * 1024;
+
+// Accepted media types and their file extensions
+pub const MEDIA_TYPES: [(&str, &str); 9] = [
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/avif", "avif"),
+    ("video/mp4", "mp4"),
+    ("video/webm", "webm"),
+    ("audio/mpeg", "mp3"),
+    ("audio/ogg", "ogg"),
+];
+
+pub fn attachment_type(media_type: &str) -> &'static str {
+    if media_type.starts_with("image/") {
+        "image"
+    } else if media_type.starts_with("gifv/") {
+        "gifv"
+    } else if media_type.starts_with("video/") {
+        "video"
+    } else if media_type.starts_with("audio/") {
+        "audio"
+    } else {
+        "unknown"
+    }
+}
+
+// Body limit for multipart forms carrying up to `files` uploads
+#[cfg(any(feature = "web", feature = "api"))]
+pub fn body_limit(max_media_bytes: usize, files: usize) -> usize {
+    max_media_bytes * files + FORM_SLACK_BYTES
+}
+
+// Read a text field without going over the size limit
+#[cfg(any(feature = "web", feature = "api"))]
+pub async fn read_text_field(
+    mut field: axum::extract::multipart::Field<'_>,
+) -> Result<String, String> {
+    let mut data = Vec::new();
+    while let Ok(Some(chunk)) = field.chunk().await {
+        if data.len() + chunk.len() > MAX_TEXT_FIELD_BYTES {
+            return Err("Field is too large".to_string());
+        }
+        data.extend_from_slice(&chunk);
+    }
+    String::from_utf8(data).map_err(|_| "Field is not valid UTF-8".to_string())
+}
+
+// Read an uploaded file without going over the size limit
+#[cfg(any(feature = "web", feature = "api"))]
+pub async fn read_field(
+    state: &AppState,
+    mut field: axum::extract::multipart::Field<'_>,
+) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    while let Ok(Some(chunk)) = field.chunk().await {
+        if data.len() + chunk.len() > state.config.max_media_bytes {
+            return Err("File is too large".to_string());
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+pub async fn add(
+    state: &AppState,
+    user_id: i64,
+    media_type: &str,
+    data: &[u8],
+    description: Option<String>,
+) -> Result<i64, String> {
+    let Some((media_type, extension)) = MEDIA_TYPES.iter().find(|(t, _)| *t == media_type) else {
+        return Err("Unsupported media type".to_string());
+    };
+    if data.is_empty() {
+        return Err("File is empty".to_string());
+    }
+    if data.len() > state.config.max_media_bytes {
+        return Err("File is too large".to_string());
+    }
+
+    // Save file
+    let id = utils::gen_unique_id();
+    let file_name = format!("{}.{}", id, extension);
+    let path = Path::new(&state.config.media_dir).join(&file_name);
+    if tokio::fs::write(&path, data).await.is_err() {
+        return Err("Failed to save file".to_string());
+    }
+
+    let description = description
+        .map(|description| description.trim().to_string())
+        .filter(|description| !description.is_empty());
+    queries::media::create(
+        state,
+        id,
+        user_id,
+        &file_name,
+        media_type,
+        description,
+        &utils::date_now(),
+    )
+    .await;
+
+    Ok(id)
+}
+
+// Media must belong to the user and not be attached yet
+pub async fn get_unattached(
+    state: &AppState,
+    user_id: i64,
+    ids: &[i64],
+) -> Result<Vec<MediaRecord>, String> {
+    if ids.len() > MAX_ATTACHMENTS {
+        return Err(format!("At most {} attachments allowed", MAX_ATTACHMENTS));
+    }
+
+    let user = queries::user::get_by_id(state, user_id).await;
+    let mut media = Vec::new();
+    for id in ids {
+        let Some(record) = queries::media::get_by_id(state, *id).await else {
+            return Err("Media not found".to_string());
+        };
+        if record.user_id != user_id || record.note_id.is_some() {
+            return Err("Media cannot be attached".to_string());
+        }
+        // Avatars and headers are unattached media too
+        let url = utils::media_url(&state.domain, &record.file_name);
+        if user.avatar_url.as_ref() == Some(&url) || user.header_url.as_ref() == Some(&url) {
+            return Err("Media cannot be attached".to_string());
+        }
+        media.push(record);
+    }
+    Ok(media)
+}
+
+// Returns the URL of an uploaded avatar or header
+pub async fn add_profile_image(
+    state: &AppState,
+    user_id: i64,
+    media_type: &str,
+    data: &[u8],
+) -> Result<String, String> {
+    if !media_type.starts_with("image/") {
+        return Err("Only images are allowed".to_string());
+    }
+    let id = add(state, user_id, media_type, data, None).await?;
+    let record = queries::media::get_by_id(state, id).await.unwrap();
+    Ok(utils::media_url(&state.domain, &record.file_name))
+}
+
+// Delete a replaced avatar or header if it was uploaded here
+pub async fn delete_profile_image(state: &AppState, url: &str) {
+    let Some(file_name) = url.strip_prefix(&utils::media_url(&state.domain, "")) else {
+        return;
+    };
+    let Some(record) = queries::media::get_by_file_name(state, file_name).await else {
+        return;
+    };
+    let path = Path::new(&state.config.media_dir).join(&record.file_name);
+    let _ = tokio::fs::remove_file(path).await;
+    queries::media::delete(state, record.id).await;
+}
+
+pub async fn attach(state: &AppState, note_id: i64, media: &[MediaRecord]) {
+    for record in media {
+        queries::media::update_note_id(state, record.id, note_id).await;
+        queries::attachment::create(
+            state,
+            note_id,
+            Some(&record.media_type),
+            &utils::media_url(&state.domain, &record.file_name),
+            record.description.as_deref(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+    }
+}
+
+// Uploads of a note that could not be created
+pub async fn delete(state: &AppState, media: &[MediaRecord]) {
+    for record in media {
+        let path = Path::new(&state.config.media_dir).join(&record.file_name);
+        let _ = tokio::fs::remove_file(path).await;
+        queries::media::delete(state, record.id).await;
+    }
+}
+
+// Rows are removed by cascade, files are not
+pub async fn delete_files(state: &AppState, note_id: i64) {
+    for record in queries::media::get_by_note(state, note_id).await {
+        let path = Path::new(&state.config.media_dir).join(&record.file_name);
+        let _ = tokio::fs::remove_file(path).await;
+    }
+}
+
+// Attachments, avatars, headers and unattached uploads of a user
+pub async fn delete_user_files(state: &AppState, user_id: i64) {
+    for record in queries::media::get_by_user(state, user_id).await {
+        let path = Path::new(&state.config.media_dir).join(&record.file_name);
+        let _ = tokio::fs::remove_file(path).await;
+    }
+}
@@ -1,10 +1,17 @@
+pub mod attachment;
 pub mod boost;
+pub mod conversation;
+pub mod delivery;
 pub mod follow;
 pub mod init;
 pub mod like;
+pub mod media;
 pub mod note;
 pub mod notification;
+pub mod pin;
+pub mod poll;
 pub mod queries;
+pub mod quote;
 pub mod search;
 pub mod user;
 pub mod utils;
@@ -1,28 +1,57 @@
+use crate::back::attachment;
+use crate::back::conversation;
 use crate::back::init::AppState;
+use crate::back::media;
 use crate::back::notification;
+use crate::back::poll;
 use crate::back::queries;
+use crate::back::queries::note::NoteRecord;
+use crate::back::queries::user::UserRecord;
+use crate::back::quote;
 use crate::back::user;
 use crate::back::utils;
 
 use serde_json::{Value, json};
 
+pub const VISIBILITIES: [&str; 4] = ["public", "unlisted", "private", "direct"];
+const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+pub struct RemoteNote {
+    pub ap_url: String,
+    pub author_ap_url: String,
+    pub content: String,
+    pub attachments: Vec<attachment::RemoteAttachment>,
+    pub in_reply_to: Option<String>,
+    pub created_at: String,
+    pub visibility: String,
+    pub summary: Option<String>,
+    pub is_sensitive: bool,
+    pub emojis: String,
+    // Actors addressed in to/cc or tagged as Mention
+    pub mentions: Vec<String>,
+    pub hashtags: Vec<String>,
+    pub poll: Option<poll::RemotePoll>,
+    pub quote_url: Option<String>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn add(
     state: &AppState,
     id: i64,
     ap_url: &str,
     author_id: i64,
     content: &str,
-    attachments: Option<String>,
     parent_id: Option<i64>,
     parent_author_username: Option<String>,
     created_at: &str,
-    is_public: i64,
+    visibility: &str,
+    summary: Option<String>,
+    is_sensitive: bool,
 ) -> Result<(), String> {
     // Create note
+    validate(state, content, visibility)?;
     let content = utils::parse_content(state, content);
-    if content.is_empty() {
-        return Err("Content cannot be empty".to_string());
-    }
+    let (summary, is_sensitive) = normalize_summary(summary, is_sensitive);
 
     queries::note::create(
         state,
@@ -30,13 +59,15 @@ pub async fn add(
         ap_url,
         author_id,
         &content,
-        attachments,
         parent_id,
         parent_author_username,
         created_at,
-        is_public,
+        visibility,
+        summary,
+        is_sensitive,
     )
     .await;
+    add_hashtags(state, id, &utils::extract_hashtags(state, &content)).await;
 
     // Update updated_at
     queries::user::update_date(state, author_id, created_at).await;
@@ -46,24 +77,216 @@ pub async fn add(
 
     // Add notification for reply
     if let Some(parent_id) = parent_id {
-        let parent = queries::note::get_by_id(state, parent_id).await.unwrap();
-        notification::add(
-            state,
-            notification::EventType::Reply,
-            author_id,
-            parent.author_id,
-            Some(parent_id),
-        )
-        .await;
+        notify_reply(state, author_id, parent_id).await;
     }
 
     Ok(())
 }
 
+pub async fn notify_reply(state: &AppState, author_id: i64, parent_id: i64) {
+    let parent = queries::note::get_by_id(state, parent_id).await.unwrap();
+    notification::add(
+        state,
+        notification::EventType::Reply,
+        author_id,
+        parent.author_id,
+        Some(parent_id),
+    )
+    .await;
+}
+
 pub async fn deliver_create(state: &AppState, id: i64) {
     let note = queries::note::get_by_id(state, id).await.unwrap();
     let author = queries::user::get_by_id(state, note.author_id).await;
-    let note_page_url = utils::note_url(&state.domain, &author.username, id);
+
+    let (note_object, mention_inboxes, mentioned_users) =
+        build_note_object(state, &note, &author).await;
+    add_mentions(state, &note, &mentioned_users).await;
+    conversation::add_note(state, id).await;
+
+    // Add notification for mention
+    for mentioned_user in mentioned_users {
+        if mentioned_user.is_local == 1 {
+            notification::add(
+                state,
+                notification::EventType::Mention,
+                note.author_id,
+                mentioned_user.id,
+                Some(id),
+            )
+            .await;
+        }
+    }
+
+    let create_id = format!("{}#create-{}", author.ap_url, utils::gen_unique_id());
+    let create_activity = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": create_id,
+        "type": "Create",
+        "actor": author.ap_url,
+        "object": note_object,
+    });
+    let json_body = create_activity.to_string();
+
+    deliver(state, &note, mention_inboxes, &json_body).await;
+}
+
+pub async fn deliver_update(state: &AppState, id: i64) {
+    let note = queries::note::get_by_id(state, id).await.unwrap();
+    let author = queries::user::get_by_id(state, note.author_id).await;
+
+    let (mut note_object, mention_inboxes, mentioned_users) =
+        build_note_object(state, &note, &author).await;
+    add_mentions(state, &note, &mentioned_users).await;
+    note_object["updated"] = json!(note.updated_at);
+
+    let update_id = format!("{}#update-{}", author.ap_url, utils::gen_unique_id());
+    let update_activity = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": update_id,
+        "type": "Update",
+        "actor": author.ap_url,
+        "object": note_object,
+    });
+    let json_body = update_activity.to_string();
+
+    deliver(state, &note, mention_inboxes, &json_body).await;
+}
+
+// Direct notes go to mentioned users only
+async fn deliver(state: &AppState, note: &NoteRecord, mention_inboxes: Vec<String>, body: &str) {
+    if note.visibility == "direct" {
+        utils::deliver_to_mentions(state, note.author_id, mention_inboxes, body).await;
+    } else {
+        utils::deliver_to_followers(state, note.author_id, mention_inboxes, body).await;
+    }
+}
+
+// Record mentioned users and the parent author as recipients
+async fn add_mentions(state: &AppState, note: &NoteRecord, mentioned_users: &[UserRecord]) {
+    for mentioned_user in mentioned_users {
+        queries::mention::create(state, note.id, mentioned_user.id).await;
+    }
+    if let Some(parent_id) = note.parent_id
+        && let Some(parent) = queries::note::get_by_id(state, parent_id).await
+    {
+        queries::mention::create(state, note.id, parent.author_id).await;
+    }
+}
+
+pub async fn add_remote_mentions(state: &AppState, note_id: i64, mentions: &[String]) {
+    for mention in mentions {
+        if let Some(mentioned_user) = queries::user::get_by_ap_url(state, mention).await {
+            queries::mention::create(state, note_id, mentioned_user.id).await;
+        }
+    }
+}
+
+// Checked before saving uploads so that a rejected note leaves no files behind
+pub fn validate(state: &AppState, content: &str, visibility: &str) -> Result<(), String> {
+    if utils::parse_content(state, content).is_empty() {
+        return Err("Content cannot be empty".to_string());
+    }
+    if !VISIBILITIES.contains(&visibility) {
+        return Err("Invalid visibility".to_string());
+    }
+    Ok(())
+}
+
+pub fn check_length(state: &AppState, content: &str) -> Result<(), String> {
+    if content.trim().chars().count() > state.config.max_note_chars {
+        return Err(format!(
+            "Content must be at most {} characters",
+            state.config.max_note_chars
+        ));
+    }
+    Ok(())
+}
+
+// Content warnings always mark the note as sensitive
+pub fn normalize_summary(summary: Option<String>, is_sensitive: bool) -> (Option<String>, i64) {
+    let summary = summary
+        .map(|summary| summary.trim().to_string())
+        .filter(|summary| !summary.is_empty());
+    let is_sensitive = is_sensitive || summary.is_some();
+    (summary, is_sensitive as i64)
+}
+
+pub async fn add_hashtags(state: &AppState, note_id: i64, hashtags: &[String]) {
+    for hashtag in hashtags {
+        queries::hashtag::create(state, note_id, hashtag).await;
+    }
+}
+
+// Remote notes keep hashtags from the text and from Hashtag tags
+pub async fn add_remote_hashtags(
+    state: &AppState,
+    note_id: i64,
+    content: &str,
+    hashtags: &[String],
+) {
+    add_hashtags(state, note_id, &utils::extract_hashtags(state, content)).await;
+    add_hashtags(state, note_id, hashtags).await;
+}
+
+async fn get_mentioned_users(state: &AppState, content: &str) -> Vec<UserRecord> {
+    let mention_usernames = utils::strip_content(state, content)
+        .split_whitespace()
+        .filter(|word| word.starts_with('@'))
+        .map(|mention| mention.trim_start_matches('@').to_string())
+        .collect::<Vec<String>>();
+
+    queries::user::get_by_username_in(state, &mention_usernames).await
+}
+
+// Returns to and cc for the visibility
+pub fn addressing(
+    visibility: &str,
+    followers_url: String,
+    mentions: Vec<String>,
+) -> (Vec<String>, Vec<String>) {
+    let public = PUBLIC.to_string();
+    match visibility {
+        "unlisted" => (vec![followers_url], [vec![public], mentions].concat()),
+        "private" => (vec![followers_url], mentions),
+        "direct" => (mentions, vec![]),
+        _ => (vec![public], [vec![followers_url], mentions].concat()),
+    }
+}
+
+pub async fn can_view(state: &AppState, note: &NoteRecord, viewer_id: Option<i64>) -> bool {
+    if note.visibility == "public" || note.visibility == "unlisted" {
+        return true;
+    }
+    let Some(viewer_id) = viewer_id else {
+        return false;
+    };
+    if note.author_id == viewer_id {
+        return true;
+    }
+
+    // Mentioned users can always see
+    if queries::mention::get(state, note.id, viewer_id)
+        .await
+        .is_some()
+    {
+        return true;
+    }
+
+    // Accepted followers can see followers-only notes
+    note.visibility == "private"
+        && queries::follow::get(state, viewer_id, note.author_id)
+            .await
+            .is_some_and(|follow| follow.pending == 0)
+}
+
+// Returns Note object, inboxes of mentioned users and mentioned users
+pub async fn build_note_object(
+    state: &AppState,
+    note: &NoteRecord,
+    author: &UserRecord,
+) -> (Value, Vec<String>, Vec<UserRecord>) {
+    let note_page_url = utils::note_url(&state.domain, &author.username, note.id);
 
     let mut note_object = json!({
         "@context": "https://www.w3.org/ns/activitystreams",
@@ -73,47 +296,31 @@ pub async fn deliver_create(state: &AppState, id: i64) {
         "content": note.content,
         "published": note.created_at,
         "url": note_page_url,
+        "summary": note.summary,
+        "sensitive": note.is_sensitive == 1,
     });
 
-    let mut to: Vec<String> = vec!["https://www.w3.org/ns/activitystreams#Public".to_string()];
+    let mut mentions: Vec<String> = vec![];
     let mut tag: Vec<Value> = vec![];
 
     // Get mentions
     let mut mention_inboxes = Vec::new();
-    let mention_usernames = utils::strip_content(state, &note.content)
-        .split_whitespace()
-        .filter(|word| word.starts_with('@'))
-        .map(|mention| mention.trim_start_matches('@').to_string())
-        .collect::<Vec<String>>();
-
-    let mentioned_users = queries::user::get_by_username_in(state, &mention_usernames).await;
-    for mentioned_user in mentioned_users {
-        to.push(mentioned_user.ap_url.clone());
-        mention_inboxes.push(mentioned_user.inbox_url);
+    let mentioned_users = get_mentioned_users(state, &note.content).await;
+    for mentioned_user in &mentioned_users {
+        mentions.push(mentioned_user.ap_url.clone());
+        mention_inboxes.push(mentioned_user.inbox_url.clone());
         tag.push(json!({
             "type": "Mention",
             "href": mentioned_user.ap_url,
             "name": &format!("@{}", mentioned_user.username),
         }));
-
-        // Add notification for mention
-        if mentioned_user.is_local == 1 {
-            notification::add(
-                state,
-                notification::EventType::Mention,
-                note.author_id,
-                mentioned_user.id,
-                Some(id),
-            )
-            .await;
-        }
     }
 
     // Get parent
     if let Some(parent_id) = note.parent_id {
         let parent = queries::note::get_by_id(state, parent_id).await.unwrap();
         let parent_author = queries::user::get_by_id(state, parent.author_id).await;
-        to.push(parent_author.ap_url.clone());
+        mentions.push(parent_author.ap_url.clone());
         mention_inboxes.push(parent_author.inbox_url);
         note_object["inReplyTo"] = json!(parent.ap_url);
         tag.push(json!({
@@ -123,20 +330,83 @@ pub async fn deliver_create(state: &AppState, id: i64) {
         }));
     }
 
+    let followers_url = utils::local_user_followers_url(&state.domain, &author.username);
+    let (to, cc) = addressing(&note.visibility, followers_url, mentions);
+    // Get hashtags
+    for hashtag in queries::hashtag::get_by_note(state, note.id).await {
+        tag.push(json!({
+            "type": "Hashtag",
+            "href": utils::hashtag_url(&state.domain, &hashtag.name),
+            "name": &format!("#{}", hashtag.name),
+        }));
+    }
+
     note_object["to"] = json!(to);
+    note_object["cc"] = json!(cc);
     note_object["tag"] = json!(tag);
+    note_object["attachment"] = json!(attachment::document_objects(state, note.id).await);
 
-    let create_id = format!("{}#create-{}", author.ap_url, utils::gen_unique_id());
-    let create_activity = json!({
-        "@context": "https://www.w3.org/ns/activitystreams",
-        "id": create_id,
-        "type": "Create",
-        "actor": author.ap_url,
-        "object": note_object,
-    });
-    let json_body = create_activity.to_string();
+    poll::add_question_fields(state, note.id, &mut note_object).await;
+    quote::add_quote_fields(state, note.id, &mut note_object).await;
 
-    utils::deliver_to_followers(state, note.author_id, mention_inboxes, &json_body).await;
+    (note_object, mention_inboxes, mentioned_users)
+}
+
+pub async fn update(
+    state: &AppState,
+    id: i64,
+    content: &str,
+    summary: Option<String>,
+    is_sensitive: bool,
+) -> Result<bool, String> {
+    check_length(state, content)?;
+    let content = utils::parse_content(state, content);
+    let (summary, is_sensitive) = normalize_summary(summary, is_sensitive);
+    if content.is_empty() {
+        return Err("Content cannot be empty".to_string());
+    }
+
+    // Get note
+    let Some(note) = queries::note::get_by_id(state, id).await else {
+        return Err("Note not found".to_string());
+    };
+    if note.boosted_id.is_some() {
+        return Err("Cannot edit a boost".to_string());
+    }
+
+    // Skip if nothing changed
+    if note.content == content && note.summary == summary && note.is_sensitive == is_sensitive {
+        return Ok(false);
+    }
+
+    // Keep previous version in history
+    let previous_date = note.updated_at.unwrap_or(note.created_at);
+    let attachments = queries::attachment::get_by_note(state, note.id).await;
+    queries::note::create_edit(
+        state,
+        note.id,
+        &note.content,
+        serde_json::to_string(&attachments).ok(),
+        note.summary.clone(),
+        note.is_sensitive,
+        &previous_date,
+    )
+    .await;
+
+    // Update
+    queries::note::update(
+        state,
+        note.id,
+        &content,
+        summary,
+        is_sensitive,
+        &utils::date_now(),
+    )
+    .await;
+    queries::hashtag::delete_by_note(state, note.id).await;
+    add_hashtags(state, note.id, &utils::extract_hashtags(state, &content)).await;
+
+    Ok(true)
 }
 
 const MAX_RECURSION_DEPTH: u32 = 16;
@@ -156,52 +426,67 @@ pub async fn add_remote(state: &AppState, ap_url: &str, depth: u32) -> Result<i6
     };
 
     // Parse
-    let Ok((note_ap_url, author_ap_url, content, attachments, in_reply_to, created_at, is_public)) =
-        parse_from_json(state, &note_json).await
-    else {
+    let Ok(remote_note) = parse_from_json(state, &note_json).await else {
         return Err("Failed to parse note JSON".to_string());
     };
-
-    let content = utils::parse_content(state, &content);
+    let note_ap_url = &remote_note.ap_url;
+    let author_ap_url = &remote_note.author_ap_url;
+    let in_reply_to = &remote_note.in_reply_to;
 
     // Check if already exists
-    if let Some(existing) = queries::note::get_by_ap_url(state, &note_ap_url).await {
+    if let Some(existing) = queries::note::get_by_ap_url(state, note_ap_url).await {
         return Ok(existing.id);
     }
 
     // Create author user if not exists
-    let author = if let Some(author) = queries::user::get_by_ap_url(state, &author_ap_url).await {
+    let author = if let Some(author) = queries::user::get_by_ap_url(state, author_ap_url).await {
         author
     } else {
-        let res = user::add_remote(state, &author_ap_url).await;
+        let res = user::add_remote(state, author_ap_url).await;
         if let Err(e) = res {
             return Err(format!("Failed to add remote author: {}", e));
         }
-        queries::user::get_by_ap_url(state, &author_ap_url)
+        queries::user::get_by_ap_url(state, author_ap_url)
             .await
             .unwrap()
     };
 
     // Fetch parent note recursively
-    if let Some(in_reply_to) = &in_reply_to {
+    if let Some(in_reply_to) = in_reply_to {
         let _res = add_remote(state, in_reply_to, depth + 1).await;
     }
 
+    add_parsed_remote(state, &remote_note, author.id, depth).await
+}
+
+// Store a parsed remote note with its mentions, attachments, emojis, hashtags, poll and quote
+pub async fn add_parsed_remote(
+    state: &AppState,
+    remote_note: &RemoteNote,
+    author_id: i64,
+    depth: u32,
+) -> Result<i64, String> {
+    // Media-only notes, polls and quotes can have empty content
+    let content = utils::parse_content(state, &remote_note.content);
+    if content.is_empty()
+        && remote_note.attachments.is_empty()
+        && remote_note.poll.is_none()
+        && remote_note.quote_url.is_none()
+    {
+        return Err("Content cannot be empty".to_string());
+    }
+
     // Get parent author username
-    let parent_id: Option<i64>;
-    let parent_author_username: Option<String>;
-    if let Some(in_reply_to) = &in_reply_to {
-        if let Some(parent) = queries::note::get_by_ap_url(state, in_reply_to).await {
-            parent_id = Some(parent.id);
-            let parent_author = queries::user::get_by_id(state, parent.author_id).await;
-            parent_author_username = Some(parent_author.username);
-        } else {
-            parent_id = None;
-            parent_author_username = None;
-        };
+    let parent = if let Some(in_reply_to) = &remote_note.in_reply_to {
+        queries::note::get_by_ap_url(state, in_reply_to).await
     } else {
-        parent_id = None;
-        parent_author_username = None;
+        None
+    };
+    let (parent_id, parent_author_username) = if let Some(parent) = parent {
+        let parent_author = queries::user::get_by_id(state, parent.author_id).await;
+        (Some(parent.id), Some(parent_author.username))
+    } else {
+        (None, None)
     };
 
     // Create
@@ -209,40 +494,37 @@ pub async fn add_remote(state: &AppState, ap_url: &str, depth: u32) -> Result<i6
     queries::note::create(
         state,
         note_id,
-        &note_ap_url,
-        author.id,
+        &remote_note.ap_url,
+        author_id,
         &content,
-        attachments,
         parent_id,
         parent_author_username,
-        &created_at,
-        is_public,
+        &remote_note.created_at,
+        &remote_note.visibility,
+        remote_note.summary.clone(),
+        remote_note.is_sensitive as i64,
     )
     .await;
+    add_remote_mentions(state, note_id, &remote_note.mentions).await;
+    attachment::add_remote(state, note_id, &remote_note.attachments).await;
+    queries::note::update_emojis(state, note_id, &remote_note.emojis).await;
+    add_remote_hashtags(state, note_id, &content, &remote_note.hashtags).await;
+    if let Some(remote_poll) = &remote_note.poll {
+        poll::add_remote(state, note_id, remote_poll).await;
+    }
+    if let Some(quote_url) = &remote_note.quote_url {
+        quote::add_remote(state, note_id, quote_url, depth).await;
+    }
 
     // Increment note count
-    queries::user::increment_note_count(state, author.id).await;
+    queries::user::increment_note_count(state, author_id).await;
 
     Ok(note_id)
 }
 
-pub async fn parse_from_json(
-    state: &AppState,
-    note_json: &Value,
-) -> Result<
-    (
-        String,
-        String,
-        String,
-        Option<String>,
-        Option<String>,
-        String,
-        i64,
-    ),
-    String,
-> {
+pub async fn parse_from_json(state: &AppState, note_json: &Value) -> Result<RemoteNote, String> {
     // Check required fields
-    if note_json["type"] != "Note" {
+    if note_json["type"] != "Note" && note_json["type"] != "Question" {
         return Err("Fetched object is not a Note".to_string());
     }
 
@@ -260,39 +542,14 @@ pub async fn parse_from_json(
 
     let content = utils::strip_content(state, content);
 
-    let mut attachments: Option<String> = None;
-    if let Some(note_attachments) = note_json["attachment"].as_array() {
-        attachments = Some("".to_string());
-        for attachment in note_attachments {
-            // Attachment type
-            let attachment_type: &str;
-            if let Some(media_type) = attachment["mediaType"].as_str() {
-                if media_type.starts_with("image/") {
-                    attachment_type = "image";
-                } else if media_type.starts_with("gifv/") {
-                    attachment_type = "gifv";
-                } else if media_type.starts_with("video/") {
-                    attachment_type = "video";
-                } else if media_type.starts_with("audio/") {
-                    attachment_type = "audio";
-                } else {
-                    attachment_type = "unknown";
-                }
-
-                attachments
-                    .as_mut()
-                    .unwrap()
-                    .push_str(&format!("{}: ", attachment_type));
-            }
+    let quote_url = quote::parse_from_json(note_json);
+    let content = if quote_url.is_some() {
+        quote::strip_fallback(&content)
+    } else {
+        content
+    };
 
-            // Attachment url
-            if let Some(url) = attachment["url"].as_str() {
-                attachments.as_mut().unwrap().push_str(url);
-                attachments.as_mut().unwrap().push('\n');
-            }
-        }
-    }
-    let attachments = attachments.map(|s| utils::parse_content(state, &s));
+    let attachments = attachment::parse_from_json(note_json);
 
     let in_reply_to = note_json["inReplyTo"]
         .as_str()
@@ -303,45 +560,96 @@ pub async fn parse_from_json(
 
     let created_at = utils::date_to_utc(created_at);
 
-    let is_public = {
-        // to is str or array.
-        if let Some(to_str) = note_json["to"].as_str() {
-            if to_str.contains("Public") { 1 } else { 0 }
+    // to and cc are str or array
+    let addressed = |field: &Value| -> Vec<String> {
+        if let Some(field_str) = field.as_str() {
+            vec![field_str.to_string()]
         } else {
-            let Some(to_array) = note_json["to"].as_array() else {
-                return Err("Note object missing to field".to_string());
-            };
-
-            to_array
-                .iter()
-                .any(|v| v.as_str().unwrap().contains("Public")) as i64
+            field
+                .as_array()
+                .map(|field_array| {
+                    field_array
+                        .iter()
+                        .filter_map(|v| v.as_str().map(|v| v.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default()
         }
     };
-    // Check also cc
-    let is_public = if is_public == 1 {
-        1
+    if !note_json["to"].is_string() && !note_json["to"].is_array() {
+        return Err("Note object missing to field".to_string());
+    }
+    let to = addressed(&note_json["to"]);
+    let cc = addressed(&note_json["cc"]);
+
+    let is_public = |v: &String| v == PUBLIC || v == "as:Public" || v == "Public";
+    let is_followers = |v: &String| v.ends_with("/followers");
+    let visibility = if to.iter().any(is_public) {
+        "public"
+    } else if cc.iter().any(is_public) {
+        "unlisted"
+    } else if to.iter().chain(cc.iter()).any(is_followers) {
+        "private"
     } else {
-        if let Some(cc_array) = note_json["cc"].as_array() {
-            cc_array
-                .iter()
-                .any(|v| v.as_str().unwrap().contains("Public")) as i64
-        } else {
-            0
-        }
+        "direct"
     };
 
-    Ok((
-        note_ap_url.to_string(),
-        author_ap_url.to_string(),
+    // Content warning
+    let summary = note_json["summary"]
+        .as_str()
+        .map(|summary| utils::strip_content(state, summary))
+        .filter(|summary| !summary.is_empty());
+    let is_sensitive = note_json["sensitive"].as_bool().unwrap_or(false) || summary.is_some();
+
+    // Mentioned actors and hashtags
+    let mut mentions: Vec<String> = vec![];
+    let mut hashtags: Vec<String> = vec![];
+    let tags = match &note_json["tag"] {
+        Value::Array(tags) => tags.iter().collect(),
+        Value::Object(_) => vec![&note_json["tag"]],
+        _ => vec![],
+    };
+    for tag in tags {
+        if tag["type"] == "Mention"
+            && let Some(href) = tag["href"].as_str()
+        {
+            mentions.push(href.to_string());
+        }
+        if tag["type"] == "Hashtag"
+            && let Some(name) = tag["name"].as_str()
+        {
+            let hashtag = name.trim_start_matches('#').to_lowercase();
+            if !hashtag.is_empty() && !hashtags.contains(&hashtag) {
+                hashtags.push(hashtag);
+            }
+        }
+    }
+    for v in to.iter().chain(cc.iter()) {
+        if !is_public(v) && !is_followers(v) && !mentions.contains(v) {
+            mentions.push(v.clone());
+        }
+    }
+
+    Ok(RemoteNote {
+        ap_url: note_ap_url.to_string(),
+        author_ap_url: author_ap_url.to_string(),
         content,
         attachments,
         in_reply_to,
         created_at,
-        is_public,
-    ))
+        visibility: visibility.to_string(),
+        summary,
+        is_sensitive,
+        emojis: utils::parse_emojis(&note_json["tag"]),
+        mentions,
+        hashtags,
+        poll: poll::parse_from_json(note_json),
+        quote_url,
+    })
 }
 
 pub async fn delete(state: &AppState, note_id: i64, user_id: i64) {
+    media::delete_files(state, note_id).await;
     queries::note::delete(state, note_id).await;
     queries::user::decrement_note_count(state, user_id).await;
 }
@@ -354,13 +662,7 @@ pub async fn deliver_delete(state: &AppState, id: i64) {
 
     // Get mentions
     let mut mention_inboxes = Vec::new();
-    let mention_usernames = utils::strip_content(state, &note.content)
-        .split_whitespace()
-        .filter(|word| word.starts_with('@'))
-        .map(|mention| mention.trim_start_matches('@').to_string())
-        .collect::<Vec<String>>();
-
-    let mentioned_users = queries::user::get_by_username_in(state, &mention_usernames).await;
+    let mentioned_users = get_mentioned_users(state, &note.content).await;
     for mentioned_user in mentioned_users {
         mention_inboxes.push(mentioned_user.inbox_url);
     }
@@ -381,5 +683,46 @@ pub async fn deliver_delete(state: &AppState, id: i64) {
     });
     let json_body = delete_activity.to_string();
 
-    utils::deliver_to_followers(state, note.author_id, mention_inboxes, &json_body).await;
+    deliver(state, &note, mention_inboxes, &json_body).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOLLOWERS: &str = "https://local.example/users/alice/followers";
+    const BOB: &str = "https://remote.example/users/bob";
+
+    fn address(visibility: &str) -> (Vec<String>, Vec<String>) {
+        addressing(visibility, FOLLOWERS.to_string(), vec![BOB.to_string()])
+    }
+
+    #[test]
+    fn addressing_public() {
+        assert_eq!(
+            address("public"),
+            (vec![PUBLIC.into()], vec![FOLLOWERS.into(), BOB.into()])
+        );
+    }
+
+    #[test]
+    fn addressing_unlisted() {
+        assert_eq!(
+            address("unlisted"),
+            (vec![FOLLOWERS.into()], vec![PUBLIC.into(), BOB.into()])
+        );
+    }
+
+    #[test]
+    fn addressing_private() {
+        assert_eq!(
+            address("private"),
+            (vec![FOLLOWERS.into()], vec![BOB.into()])
+        );
+    }
+
+    #[test]
+    fn addressing_direct() {
+        assert_eq!(address("direct"), (vec![BOB.into()], vec![]));
+    }
 }
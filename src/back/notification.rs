@@ -8,6 +8,7 @@ pub enum EventType {
     Like,
     Boost,
     Mention,
+    FollowRequest,
 }
 
 impl From<EventType> for i64 {
@@ -18,6 +19,7 @@ impl From<EventType> for i64 {
             EventType::Like => 3,
             EventType::Boost => 4,
             EventType::Mention => 5,
+            EventType::FollowRequest => 6,
         }
     }
 }
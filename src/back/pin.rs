@@ -0,0 +1,191 @@
+use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::queries;
+use crate::back::queries::user::UserRecord;
+use crate::back::utils;
+
+use serde_json::{Value, json};
+
+pub const MAX_PINS: i64 = 5;
+
+pub async fn pin(state: &AppState, user_id: i64, note_id: i64) -> Result<(), String> {
+    // Check if already pinned
+    let existing = queries::pin::get(state, user_id, note_id).await;
+    if existing.is_some() {
+        return Err("Already pinned".to_string());
+    }
+
+    // Get note
+    let Some(note) = queries::note::get_by_id(state, note_id).await else {
+        return Err("Note not found".to_string());
+    };
+    if note.author_id != user_id || note.boosted_id.is_some() {
+        return Err("Only your own notes can be pinned".to_string());
+    }
+    if note.visibility != "public" && note.visibility != "unlisted" {
+        return Err("Only public notes can be pinned".to_string());
+    }
+    if queries::pin::count(state, user_id).await >= MAX_PINS {
+        return Err(format!("At most {} notes can be pinned", MAX_PINS));
+    }
+
+    // Pin
+    queries::pin::create(state, user_id, note_id, &utils::date_now()).await;
+
+    Ok(())
+}
+
+pub async fn unpin(state: &AppState, user_id: i64, note_id: i64) -> Result<(), String> {
+    // Check if pinned
+    let existing = queries::pin::get(state, user_id, note_id).await;
+    if existing.is_none() {
+        return Err("Not pinned".to_string());
+    }
+
+    // Unpin
+    queries::pin::delete(state, user_id, note_id).await;
+
+    Ok(())
+}
+
+// Add or Remove on the featured collection
+async fn deliver_featured(state: &AppState, user_id: i64, note_id: i64, activity_type: &str) {
+    let user = queries::user::get_by_id(state, user_id).await;
+    let note = queries::note::get_by_id(state, note_id).await.unwrap();
+
+    let activity_id = format!(
+        "{}#{}-{}",
+        user.ap_url,
+        activity_type.to_lowercase(),
+        utils::gen_unique_id()
+    );
+    let activity = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": activity_id,
+        "type": activity_type,
+        "actor": user.ap_url,
+        "object": note.ap_url,
+        "target": utils::local_user_featured_url(&state.domain, &user.username),
+    });
+    let json_body = activity.to_string();
+
+    utils::deliver_to_followers(state, user_id, vec![], &json_body).await;
+}
+
+pub async fn deliver_pin(state: &AppState, user_id: i64, note_id: i64) {
+    deliver_featured(state, user_id, note_id, "Add").await;
+}
+
+pub async fn deliver_unpin(state: &AppState, user_id: i64, note_id: i64) {
+    deliver_featured(state, user_id, note_id, "Remove").await;
+}
+
+// Replace pins of a remote user with the items of their featured collection
+pub async fn fetch_remote(state: &AppState, user_id: i64) -> Result<(), String> {
+    let user = queries::user::get_by_id(state, user_id).await;
+
+    // Get featured collection URL
+    let Some(featured_url) = fetch_featured_url(state, &user.ap_url).await? else {
+        return Ok(());
+    };
+
+    // Fetch collection, items may be on the first page
+    let Ok(res) = utils::signed_get(state, &featured_url).await else {
+        return Err("Failed to fetch featured collection".to_string());
+    };
+    let Ok(collection_json) = res.json::<Value>().await else {
+        return Err("Fetched object is not valid JSON".to_string());
+    };
+    let items = if let Some(items) = collection_json["orderedItems"].as_array() {
+        items.clone()
+    } else if let Some(items) = collection_json["first"]["orderedItems"].as_array() {
+        items.clone()
+    } else {
+        vec![]
+    };
+
+    let mut note_ids = vec![];
+    for item in items.iter().take(MAX_PINS as usize) {
+        let Some(note_ap_url) = item.as_str().or(item["id"].as_str()) else {
+            continue;
+        };
+        let Ok(note_id) = get_or_add_note(state, note_ap_url).await else {
+            continue;
+        };
+        let note = queries::note::get_by_id(state, note_id).await.unwrap();
+        if note.author_id == user.id {
+            note_ids.push(note_id);
+        }
+    }
+
+    // Collections list the newest pin first, so insert from the end
+    queries::pin::delete_by_user(state, user.id).await;
+    let date_now = utils::date_now();
+    for note_id in note_ids.iter().rev() {
+        queries::pin::create(state, user.id, *note_id, &date_now).await;
+    }
+
+    Ok(())
+}
+
+async fn fetch_featured_url(state: &AppState, ap_url: &str) -> Result<Option<String>, String> {
+    let Ok(res) = utils::signed_get(state, ap_url).await else {
+        return Err("Failed to fetch remote user".to_string());
+    };
+    let Ok(user_json) = res.json::<Value>().await else {
+        return Err("Fetched object is not valid JSON".to_string());
+    };
+    Ok(user_json["featured"].as_str().map(|s| s.to_string()))
+}
+
+// Add and Remove targets other than the actor's featured collection are ignored
+pub async fn is_featured_target(state: &AppState, ap_url: &str, target: &Value) -> bool {
+    let Some(target) = target.as_str().or(target["id"].as_str()) else {
+        return false;
+    };
+    fetch_featured_url(state, ap_url)
+        .await
+        .is_ok_and(|featured_url| featured_url.as_deref() == Some(target))
+}
+
+// Inbound Add and Remove only touch notes by the actor
+pub async fn add_remote(
+    state: &AppState,
+    user: &UserRecord,
+    note_ap_url: &str,
+    target: &Value,
+) -> Result<(), String> {
+    // Same cap as fetch_remote, checked before fetching the actor
+    if queries::pin::count(state, user.id).await >= MAX_PINS {
+        return Err(format!("At most {} notes can be pinned", MAX_PINS));
+    }
+    if !is_featured_target(state, &user.ap_url, target).await {
+        return Err("Target is not the featured collection".to_string());
+    }
+
+    let note_id = get_or_add_note(state, note_ap_url).await?;
+    let note = queries::note::get_by_id(state, note_id).await.unwrap();
+    if note.author_id != user.id {
+        return Err("Note is not by the actor".to_string());
+    }
+    if note.visibility != "public" && note.visibility != "unlisted" {
+        return Err("Only public notes can be pinned".to_string());
+    }
+
+    queries::pin::create(state, user.id, note_id, &utils::date_now()).await;
+
+    Ok(())
+}
+
+pub async fn remove_remote(state: &AppState, user_id: i64, note_ap_url: &str) {
+    if let Some(note) = queries::note::get_by_ap_url(state, note_ap_url).await {
+        queries::pin::delete(state, user_id, note.id).await;
+    }
+}
+
+async fn get_or_add_note(state: &AppState, note_ap_url: &str) -> Result<i64, String> {
+    if let Some(note) = queries::note::get_by_ap_url(state, note_ap_url).await {
+        return Ok(note.id);
+    }
+    note::add_remote(state, note_ap_url, 0).await
+}
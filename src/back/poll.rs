@@ -0,0 +1,451 @@
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::back::utils;
+
+use serde_json::{Value, json};
+
+pub const MAX_OPTIONS: usize = 4;
+pub const MAX_OPTION_CHARS: usize = 50;
+pub const MIN_EXPIRATION: i64 = 300;
+pub const MAX_EXPIRATION: i64 = 2629746;
+
+pub struct RemotePoll {
+    pub is_multiple: bool,
+    // Option titles with vote counts
+    pub options: Vec<(String, i64)>,
+    pub expires_at: Option<String>,
+    pub voters_count: i64,
+}
+
+#[derive(serde::Serialize)]
+pub struct PollView {
+    pub id: i64,
+    pub is_multiple: bool,
+    pub expires_at: Option<String>,
+    pub is_expired: bool,
+    pub voters_count: i64,
+    pub votes_count: i64,
+    pub options: Vec<PollOptionView>,
+    pub own_votes: Vec<i64>,
+    pub can_vote: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct PollOptionView {
+    pub position: i64,
+    pub title: String,
+    pub votes_count: i64,
+    pub percent: i64,
+}
+
+// Returns trimmed options
+pub fn validate(options: &[String], expires_in: i64) -> Result<Vec<String>, String> {
+    let options = options
+        .iter()
+        .map(|option| option.trim().to_string())
+        .filter(|option| !option.is_empty())
+        .collect::<Vec<String>>();
+    if options.len() < 2 {
+        return Err("Poll needs at least 2 options".to_string());
+    }
+    if options.len() > MAX_OPTIONS {
+        return Err(format!("Poll can have at most {} options", MAX_OPTIONS));
+    }
+    if options
+        .iter()
+        .any(|option| option.chars().count() > MAX_OPTION_CHARS)
+    {
+        return Err(format!(
+            "Poll options must be at most {} characters",
+            MAX_OPTION_CHARS
+        ));
+    }
+    for (i, option) in options.iter().enumerate() {
+        if options[..i].contains(option) {
+            return Err("Poll options must be unique".to_string());
+        }
+    }
+    if !(MIN_EXPIRATION..=MAX_EXPIRATION).contains(&expires_in) {
+        return Err("Invalid poll expiration".to_string());
+    }
+
+    Ok(options)
+}
+
+pub async fn add(
+    state: &AppState,
+    note_id: i64,
+    options: &[String],
+    expires_in: i64,
+    is_multiple: bool,
+) -> Result<(), String> {
+    let options = validate(options, expires_in)?;
+
+    let expires_at = utils::date_plus_seconds(expires_in);
+    queries::poll::upsert(state, note_id, is_multiple as i64, Some(expires_at), 0).await;
+    let poll = queries::poll::get_by_note(state, note_id).await.unwrap();
+    for (position, title) in options.iter().enumerate() {
+        queries::poll::upsert_option(state, poll.id, position as i64, title, 0).await;
+    }
+
+    Ok(())
+}
+
+// Store or refresh the counts of a remote poll
+pub async fn add_remote(state: &AppState, note_id: i64, remote_poll: &RemotePoll) {
+    queries::poll::upsert(
+        state,
+        note_id,
+        remote_poll.is_multiple as i64,
+        remote_poll.expires_at.clone(),
+        remote_poll.voters_count,
+    )
+    .await;
+    let poll = queries::poll::get_by_note(state, note_id).await.unwrap();
+    for (position, (title, votes_count)) in remote_poll.options.iter().enumerate() {
+        queries::poll::upsert_option(state, poll.id, position as i64, title, *votes_count).await;
+    }
+    queries::poll::delete_options_from(state, poll.id, remote_poll.options.len() as i64).await;
+}
+
+pub fn parse_from_json(note_json: &Value) -> Option<RemotePoll> {
+    if note_json["type"] != "Question" {
+        return None;
+    }
+
+    // anyOf for multiple choice, oneOf for single choice
+    let (choices, is_multiple) = if let Some(any_of) = note_json["anyOf"].as_array() {
+        (any_of, true)
+    } else {
+        (note_json["oneOf"].as_array()?, false)
+    };
+    let options = choices
+        .iter()
+        .filter_map(|choice| {
+            let title = choice["name"].as_str()?;
+            let votes_count = choice["replies"]["totalItems"].as_i64().unwrap_or(0);
+            Some((title.to_string(), votes_count))
+        })
+        .collect::<Vec<(String, i64)>>();
+    if options.is_empty() {
+        return None;
+    }
+
+    let expires_at = note_json["endTime"]
+        .as_str()
+        .or(note_json["closed"].as_str())
+        .map(utils::date_to_utc);
+
+    // Single choice polls have one vote per voter
+    let voters_count = note_json["votersCount"].as_i64().unwrap_or(if is_multiple {
+        0
+    } else {
+        options.iter().map(|(_, votes_count)| votes_count).sum()
+    });
+
+    Some(RemotePoll {
+        is_multiple,
+        options,
+        expires_at,
+        voters_count,
+    })
+}
+
+pub fn is_expired(expires_at: &Option<String>) -> bool {
+    expires_at
+        .as_ref()
+        .is_some_and(|expires_at| utils::is_past(expires_at))
+}
+
+pub async fn get_view(state: &AppState, note_id: i64, viewer_id: Option<i64>) -> Option<PollView> {
+    let poll = queries::poll::get_by_note(state, note_id).await?;
+    let options = queries::poll::get_options(state, poll.id).await;
+    let votes_count = options.iter().map(|option| option.votes_count).sum::<i64>();
+
+    let own_votes = if let Some(viewer_id) = viewer_id {
+        queries::poll::get_votes(state, poll.id, viewer_id)
+            .await
+            .into_iter()
+            .map(|vote| vote.position)
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let is_expired = is_expired(&poll.expires_at);
+    let is_author = if let Some(viewer_id) = viewer_id {
+        let note = queries::note::get_by_id(state, poll.note_id).await?;
+        note.author_id == viewer_id
+    } else {
+        false
+    };
+    let can_vote = viewer_id.is_some() && !is_expired && !is_author && own_votes.is_empty();
+
+    let options = options
+        .into_iter()
+        .map(|option| PollOptionView {
+            position: option.position,
+            percent: if votes_count > 0 {
+                option.votes_count * 100 / votes_count
+            } else {
+                0
+            },
+            title: option.title,
+            votes_count: option.votes_count,
+        })
+        .collect();
+
+    Some(PollView {
+        id: poll.id,
+        is_multiple: poll.is_multiple == 1,
+        expires_at: poll.expires_at,
+        is_expired,
+        voters_count: poll.voters_count,
+        votes_count,
+        options,
+        own_votes,
+        can_vote,
+    })
+}
+
+pub async fn vote(
+    state: &AppState,
+    user_id: i64,
+    note_id: i64,
+    choices: &[i64],
+) -> Result<(), String> {
+    // Get poll
+    let Some(note) = queries::note::get_by_id(state, note_id).await else {
+        return Err("Note not found".to_string());
+    };
+    let Some(poll) = queries::poll::get_by_note(state, note_id).await else {
+        return Err("Poll not found".to_string());
+    };
+    if is_expired(&poll.expires_at) {
+        return Err("Poll has ended".to_string());
+    }
+    if note.author_id == user_id {
+        return Err("Cannot vote on your own poll".to_string());
+    }
+    if !queries::poll::get_votes(state, poll.id, user_id)
+        .await
+        .is_empty()
+    {
+        return Err("Already voted".to_string());
+    }
+
+    // Check choices
+    let mut choices = choices.to_vec();
+    choices.sort();
+    choices.dedup();
+    if choices.is_empty() {
+        return Err("No choice selected".to_string());
+    }
+    if poll.is_multiple == 0 && choices.len() > 1 {
+        return Err("Only one choice allowed".to_string());
+    }
+    let options_count = queries::poll::get_options(state, poll.id).await.len() as i64;
+    if choices
+        .iter()
+        .any(|choice| *choice < 0 || *choice >= options_count)
+    {
+        return Err("Invalid choice".to_string());
+    }
+
+    // Vote
+    let date_now = utils::date_now();
+    for choice in &choices {
+        queries::poll::create_vote(state, poll.id, user_id, *choice, &date_now).await;
+        queries::poll::increment_votes_count(state, poll.id, *choice).await;
+    }
+    queries::poll::increment_voters_count(state, poll.id).await;
+
+    Ok(())
+}
+
+// Each choice is sent as a reply named after the option
+pub async fn deliver_vote(state: &AppState, user_id: i64, note_id: i64, choices: &[i64]) {
+    let user = queries::user::get_by_id(state, user_id).await;
+    let note = queries::note::get_by_id(state, note_id).await.unwrap();
+    let author = queries::user::get_by_id(state, note.author_id).await;
+    let poll = queries::poll::get_by_note(state, note_id).await.unwrap();
+    let options = queries::poll::get_options(state, poll.id).await;
+
+    for option in options
+        .iter()
+        .filter(|option| choices.contains(&option.position))
+    {
+        let vote_id = format!("{}#votes/{}/{}", user.ap_url, poll.id, option.position);
+        let create_activity = json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "id": format!("{}/activity", vote_id),
+            "type": "Create",
+            "actor": user.ap_url,
+            "to": [author.ap_url],
+            "object": {
+                "id": vote_id,
+                "type": "Note",
+                "name": option.title,
+                "attributedTo": user.ap_url,
+                "inReplyTo": note.ap_url,
+                "to": [author.ap_url],
+            },
+        });
+        let json_body = create_activity.to_string();
+
+        utils::signed_deliver(state, user.id, &author.inbox_url, &json_body).await;
+    }
+}
+
+// Remote votes arrive one choice at a time
+pub async fn add_remote_vote(
+    state: &AppState,
+    user_id: i64,
+    note_id: i64,
+    name: &str,
+) -> Result<(), String> {
+    let Some(poll) = queries::poll::get_by_note(state, note_id).await else {
+        return Err("Poll not found".to_string());
+    };
+    if is_expired(&poll.expires_at) {
+        return Err("Poll has ended".to_string());
+    }
+    let Some(option) = queries::poll::get_options(state, poll.id)
+        .await
+        .into_iter()
+        .find(|option| option.title == name)
+    else {
+        return Err("Invalid choice".to_string());
+    };
+
+    let own_votes = queries::poll::get_votes(state, poll.id, user_id).await;
+    if own_votes
+        .iter()
+        .any(|vote| vote.position == option.position)
+    {
+        return Err("Already voted".to_string());
+    }
+    if poll.is_multiple == 0 && !own_votes.is_empty() {
+        return Err("Only one choice allowed".to_string());
+    }
+
+    queries::poll::create_vote(state, poll.id, user_id, option.position, &utils::date_now()).await;
+    queries::poll::increment_votes_count(state, poll.id, option.position).await;
+    if own_votes.is_empty() {
+        queries::poll::increment_voters_count(state, poll.id).await;
+    }
+
+    Ok(())
+}
+
+// Turn a Note object into a Question if the note has a poll
+pub async fn add_question_fields(state: &AppState, note_id: i64, note_object: &mut Value) {
+    let Some(poll) = queries::poll::get_by_note(state, note_id).await else {
+        return;
+    };
+    let options = queries::poll::get_options(state, poll.id).await;
+
+    let choices = options
+        .iter()
+        .map(|option| {
+            json!({
+                "type": "Note",
+                "name": option.title,
+                "replies": {
+                    "type": "Collection",
+                    "totalItems": option.votes_count,
+                },
+            })
+        })
+        .collect::<Vec<Value>>();
+    let choices_key = if poll.is_multiple == 1 {
+        "anyOf"
+    } else {
+        "oneOf"
+    };
+
+    note_object["type"] = json!("Question");
+    note_object["endTime"] = json!(poll.expires_at);
+    note_object["votersCount"] = json!(poll.voters_count);
+    note_object[choices_key] = json!(choices);
+    if is_expired(&poll.expires_at) {
+        note_object["closed"] = json!(poll.expires_at);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(titles: &[&str]) -> Vec<String> {
+        titles.iter().map(|title| title.to_string()).collect()
+    }
+
+    #[test]
+    fn validate_trims_and_drops_empty_options() {
+        let valid = validate(&options(&[" yes ", "", "no"]), MIN_EXPIRATION);
+        assert_eq!(valid, Ok(options(&["yes", "no"])));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_polls() {
+        assert!(validate(&options(&["yes"]), MIN_EXPIRATION).is_err());
+        assert!(validate(&options(&["a", "b", "c", "d", "e"]), MIN_EXPIRATION).is_err());
+        assert!(
+            validate(
+                &options(&["yes", &"a".repeat(MAX_OPTION_CHARS + 1)]),
+                MIN_EXPIRATION
+            )
+            .is_err()
+        );
+        assert!(validate(&options(&["yes", " yes"]), MIN_EXPIRATION).is_err());
+        assert!(validate(&options(&["yes", "no"]), MIN_EXPIRATION - 1).is_err());
+        assert!(validate(&options(&["yes", "no"]), MAX_EXPIRATION + 1).is_err());
+    }
+
+    #[test]
+    fn parse_from_json_single_choice() {
+        let note_json = json!({
+            "type": "Question",
+            "endTime": "2026-01-01T00:00:00Z",
+            "oneOf": [
+                {"type": "Note", "name": "yes", "replies": {"totalItems": 3}},
+                {"type": "Note", "name": "no", "replies": {"totalItems": 2}},
+            ],
+        });
+        let poll = parse_from_json(&note_json).unwrap();
+        assert!(!poll.is_multiple);
+        assert_eq!(
+            poll.options,
+            vec![("yes".to_string(), 3), ("no".to_string(), 2)]
+        );
+        assert_eq!(poll.expires_at.as_deref(), Some("2026-01-01T00:00:00Z"));
+        assert_eq!(poll.voters_count, 5);
+    }
+
+    #[test]
+    fn parse_from_json_multiple_choice() {
+        let note_json = json!({
+            "type": "Question",
+            "votersCount": 4,
+            "anyOf": [
+                {"type": "Note", "name": "a", "replies": {"totalItems": 3}},
+                {"type": "Note", "name": "b"},
+            ],
+        });
+        let poll = parse_from_json(&note_json).unwrap();
+        assert!(poll.is_multiple);
+        assert_eq!(
+            poll.options,
+            vec![("a".to_string(), 3), ("b".to_string(), 0)]
+        );
+        assert_eq!(poll.expires_at, None);
+        assert_eq!(poll.voters_count, 4);
+    }
+
+    #[test]
+    fn parse_from_json_ignores_other_objects() {
+        assert!(parse_from_json(&json!({"type": "Note", "oneOf": [{"name": "a"}]})).is_none());
+        assert!(parse_from_json(&json!({"type": "Question", "oneOf": []})).is_none());
+    }
+}
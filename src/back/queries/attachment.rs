@@ -0,0 +1,88 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow, serde::Serialize, serde::Deserialize, Clone)]
+pub struct AttachmentRecord {
+    pub note_id: i64,
+    pub media_type: Option<String>,
+    pub url: String,
+    pub description: Option<String>,
+    pub blurhash: Option<String>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub focal_point: Option<String>,
+}
+
+pub async fn get_by_note(state: &AppState, note_id: i64) -> Vec<AttachmentRecord> {
+    query_as(
+        "SELECT note_id, media_type, url, description, blurhash, width, height, focal_point FROM attachments
+        WHERE note_id = $1
+        ORDER BY id ASC",
+    )
+    .bind(note_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn get_by_note_in(state: &AppState, note_ids: &[i64]) -> Vec<AttachmentRecord> {
+    if note_ids.is_empty() {
+        return vec![];
+    }
+    let in_placeholder = (0..note_ids.len())
+        .map(|id| format!("${}", id + 1))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let query_str = format!(
+        "SELECT note_id, media_type, url, description, blurhash, width, height, focal_point FROM attachments
+        WHERE note_id IN ({})
+        ORDER BY id ASC",
+        in_placeholder
+    );
+    let mut query = query_as::<_, AttachmentRecord>(&query_str);
+    for note_id in note_ids {
+        query = query.bind(note_id);
+    }
+    query.fetch_all(&state.db_pool).await.unwrap()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create(
+    state: &AppState,
+    note_id: i64,
+    media_type: Option<&str>,
+    url: &str,
+    description: Option<&str>,
+    blurhash: Option<&str>,
+    width: Option<i64>,
+    height: Option<i64>,
+    focal_point: Option<&str>,
+) {
+    query(
+        "INSERT INTO attachments (note_id, media_type, url, description, blurhash, width, height, focal_point)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(note_id)
+    .bind(media_type)
+    .bind(url)
+    .bind(description)
+    .bind(blurhash)
+    .bind(width)
+    .bind(height)
+    .bind(focal_point)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn delete_by_note(state: &AppState, note_id: i64) {
+    query(
+        "DELETE FROM attachments
+        WHERE note_id = $1",
+    )
+    .bind(note_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
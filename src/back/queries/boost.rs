@@ -20,6 +20,7 @@ pub async fn get(state: &AppState, user_id: i64, note_id: i64) -> Option<BoostRe
     .unwrap()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create(
     state: &AppState,
     id: i64,
@@ -29,14 +30,17 @@ pub async fn create(
     boosted_username: &str,
     boosted_created_at: &str,
     content: &str,
-    attachments: Option<String>,
     parent_id: Option<i64>,
     parent_author_username: Option<String>,
     created_at: &str,
+    visibility: &str,
+    summary: Option<String>,
+    is_sensitive: i64,
+    emojis: &str,
 ) {
     query(
-        "INSERT INTO notes (id, ap_url, author_id, boosted_id, boosted_username, boosted_created_at, content, attachments, parent_id, parent_author_username, created_at, is_public)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 1)",
+        "INSERT INTO notes (id, ap_url, author_id, boosted_id, boosted_username, boosted_created_at, content, parent_id, parent_author_username, created_at, visibility, summary, is_sensitive, emojis)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
     )
     .bind(id)
     .bind(ap_url)
@@ -45,10 +49,13 @@ pub async fn create(
     .bind(boosted_username)
     .bind(boosted_created_at)
     .bind(content)
-    .bind(attachments)
     .bind(parent_id)
     .bind(parent_author_username)
     .bind(created_at)
+    .bind(visibility)
+    .bind(summary)
+    .bind(is_sensitive)
+    .bind(emojis)
     .execute(&state.db_pool)
     .await
     .unwrap();
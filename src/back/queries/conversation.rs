@@ -0,0 +1,144 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow)]
+pub struct ConversationRecord {
+    pub id: i64,
+}
+
+pub async fn get_by_participants(
+    state: &AppState,
+    participants: &str,
+) -> Option<ConversationRecord> {
+    query_as(
+        "SELECT id FROM conversations
+        WHERE participants = $1",
+    )
+    .bind(participants)
+    .fetch_optional(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn create(state: &AppState, participants: &str, created_at: &str) {
+    query(
+        "INSERT INTO conversations (participants, created_at)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(participants)
+    .bind(created_at)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+#[derive(sqlx::FromRow)]
+pub struct ConversationWithMemberRecord {
+    pub id: i64,
+    pub participants: String,
+    pub is_unread: i64,
+    pub last_note_id: i64,
+}
+
+// Conversations of the user, most recently active first
+pub async fn get_by_user(
+    state: &AppState,
+    user_id: i64,
+    until_date: &str,
+    until_id: i64,
+    limit: i64,
+) -> Vec<ConversationWithMemberRecord> {
+    query_as(
+        "SELECT c.id, c.participants, cm.is_unread, n.id AS last_note_id
+        FROM conversation_members AS cm
+        JOIN conversations AS c ON cm.conversation_id = c.id
+        JOIN notes AS n ON n.id = (
+            SELECT ln.id FROM notes AS ln
+            WHERE ln.conversation_id = c.id
+            ORDER BY ln.created_at DESC, ln.id DESC
+            LIMIT 1
+        )
+        WHERE cm.user_id = $1
+        AND ((n.created_at < $2)
+        OR (n.created_at = $2 AND n.id < $3))
+        ORDER BY n.created_at DESC, n.id DESC
+        LIMIT $4",
+    )
+    .bind(user_id)
+    .bind(until_date)
+    .bind(until_id)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn get_by_id_and_user(
+    state: &AppState,
+    id: i64,
+    user_id: i64,
+) -> Option<ConversationWithMemberRecord> {
+    query_as(
+        "SELECT c.id, c.participants, cm.is_unread, n.id AS last_note_id
+        FROM conversation_members AS cm
+        JOIN conversations AS c ON cm.conversation_id = c.id
+        JOIN notes AS n ON n.id = (
+            SELECT ln.id FROM notes AS ln
+            WHERE ln.conversation_id = c.id
+            ORDER BY ln.created_at DESC, ln.id DESC
+            LIMIT 1
+        )
+        WHERE c.id = $1
+        AND cm.user_id = $2",
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn upsert_member(state: &AppState, conversation_id: i64, user_id: i64, is_unread: i64) {
+    query(
+        "INSERT INTO conversation_members (conversation_id, user_id, is_unread)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (conversation_id, user_id) DO UPDATE
+        SET is_unread = $3",
+    )
+    .bind(conversation_id)
+    .bind(user_id)
+    .bind(is_unread)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn update_read(state: &AppState, conversation_id: i64, user_id: i64) {
+    query(
+        "UPDATE conversation_members
+        SET is_unread = 0
+        WHERE conversation_id = $1
+        AND user_id = $2",
+    )
+    .bind(conversation_id)
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+#[cfg(feature = "api")]
+pub async fn delete_member(state: &AppState, conversation_id: i64, user_id: i64) {
+    query(
+        "DELETE FROM conversation_members
+        WHERE conversation_id = $1
+        AND user_id = $2",
+    )
+    .bind(conversation_id)
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
@@ -0,0 +1,75 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow)]
+pub struct DeliveryRecord {
+    pub id: i64,
+    pub sender_ap_url: String,
+    pub private_key: Option<String>,
+    pub inbox_url: String,
+    pub body: String,
+    pub attempts: i64,
+}
+
+pub async fn get_due(state: &AppState, date_now: &str, limit: i64) -> Vec<DeliveryRecord> {
+    query_as(
+        "SELECT deliveries.id, users.ap_url AS sender_ap_url, users.private_key,
+            deliveries.inbox_url, deliveries.body, deliveries.attempts
+        FROM deliveries
+        JOIN users ON users.id = deliveries.sender_id
+        WHERE deliveries.next_attempt_at <= $1
+        ORDER BY deliveries.next_attempt_at ASC, deliveries.id ASC
+        LIMIT $2",
+    )
+    .bind(date_now)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn create(
+    state: &AppState,
+    sender_id: i64,
+    inbox_url: &str,
+    body: &str,
+    created_at: &str,
+) {
+    query(
+        "INSERT INTO deliveries (sender_id, inbox_url, body, next_attempt_at, created_at)
+        VALUES ($1, $2, $3, $4, $4)",
+    )
+    .bind(sender_id)
+    .bind(inbox_url)
+    .bind(body)
+    .bind(created_at)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn reschedule(state: &AppState, id: i64, attempts: i64, next_attempt_at: &str) {
+    query(
+        "UPDATE deliveries
+        SET attempts = $1, next_attempt_at = $2
+        WHERE id = $3",
+    )
+    .bind(attempts)
+    .bind(next_attempt_at)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn delete(state: &AppState, id: i64) {
+    query(
+        "DELETE FROM deliveries
+        WHERE id = $1",
+    )
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
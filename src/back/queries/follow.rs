@@ -5,11 +5,12 @@ use sqlx::{query, query_as};
 #[derive(sqlx::FromRow)]
 pub struct FollowRecord {
     pub pending: i64,
+    pub ap_url: Option<String>,
 }
 
 pub async fn get(state: &AppState, follower_id: i64, followee_id: i64) -> Option<FollowRecord> {
     query_as(
-        "SELECT follower_id, followee_id, pending FROM follows
+        "SELECT follower_id, followee_id, pending, ap_url FROM follows
         WHERE follower_id = $1
         AND followee_id = $2",
     )
@@ -24,6 +25,10 @@ pub async fn get(state: &AppState, follower_id: i64, followee_id: i64) -> Option
 pub struct FollowUserRecord {
     pub display_name: String,
     pub username: String,
+    pub ap_url: String,
+    pub emojis: String,
+    pub avatar_url: Option<String>,
+    pub header_url: Option<String>,
 }
 
 pub async fn get_following(
@@ -33,7 +38,7 @@ pub async fn get_following(
     limit: i64,
 ) -> Vec<FollowUserRecord> {
     query_as(
-        "SELECT users.display_name, users.username
+        "SELECT users.display_name, users.username, users.ap_url, users.emojis, users.avatar_url, users.header_url
         FROM follows
         JOIN users ON follows.followee_id = users.id
         WHERE users.username > $1
@@ -61,7 +66,7 @@ pub async fn get_following_in(
         .collect::<Vec<String>>()
         .join(", ");
     let query_str = format!(
-        "SELECT users.display_name, users.username
+        "SELECT users.display_name, users.username, users.ap_url, users.emojis, users.avatar_url, users.header_url
         FROM follows
         JOIN users ON follows.followee_id = users.id
         WHERE follows.follower_id = $1
@@ -77,6 +82,33 @@ pub async fn get_following_in(
     query.fetch_all(&state.db_pool).await.unwrap()
 }
 
+#[cfg(feature = "api")]
+pub async fn get_requested_in(
+    state: &AppState,
+    follower_id: i64,
+    followee_usernames: &Vec<String>,
+) -> Vec<FollowUserRecord> {
+    let in_placeholder = (0..followee_usernames.len())
+        .map(|id| format!("${}", id + 2))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let query_str = format!(
+        "SELECT users.display_name, users.username, users.ap_url, users.emojis, users.avatar_url, users.header_url
+        FROM follows
+        JOIN users ON follows.followee_id = users.id
+        WHERE follows.follower_id = $1
+        AND users.username IN ({}) 
+        AND follows.pending = 1",
+        in_placeholder
+    );
+    let mut query = query_as(&query_str).bind(follower_id);
+    for username in followee_usernames {
+        query = query.bind(username);
+    }
+
+    query.fetch_all(&state.db_pool).await.unwrap()
+}
+
 pub async fn get_followers(
     state: &AppState,
     followee_id: i64,
@@ -84,7 +116,7 @@ pub async fn get_followers(
     limit: i64,
 ) -> Vec<FollowUserRecord> {
     query_as(
-        "SELECT users.display_name, users.username
+        "SELECT users.display_name, users.username, users.ap_url, users.emojis, users.avatar_url, users.header_url
         FROM follows
         JOIN users ON follows.follower_id = users.id
         WHERE users.username > $1
@@ -112,7 +144,7 @@ pub async fn get_followers_in(
         .collect::<Vec<String>>()
         .join(", ");
     let query_str = format!(
-        "SELECT users.display_name, users.username
+        "SELECT users.display_name, users.username, users.ap_url, users.emojis, users.avatar_url, users.header_url
         FROM follows
         JOIN users ON follows.follower_id = users.id
         WHERE follows.followee_id = $1
@@ -128,14 +160,39 @@ pub async fn get_followers_in(
     query.fetch_all(&state.db_pool).await.unwrap()
 }
 
+pub async fn get_requests(
+    state: &AppState,
+    followee_id: i64,
+    max_username: &str,
+    limit: i64,
+) -> Vec<FollowUserRecord> {
+    query_as(
+        "SELECT users.display_name, users.username, users.ap_url, users.emojis, users.avatar_url, users.header_url
+        FROM follows
+        JOIN users ON follows.follower_id = users.id
+        WHERE users.username > $1
+        AND follows.followee_id = $2
+        AND follows.pending = 1
+        ORDER BY users.username ASC
+        LIMIT $3",
+    )
+    .bind(max_username)
+    .bind(followee_id)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
 #[derive(sqlx::FromRow)]
 pub struct FollowerInboxRecord {
     pub inbox_url: String,
+    pub shared_inbox_url: Option<String>,
 }
 
 pub async fn get_follower_inboxes(state: &AppState, followee_id: i64) -> Vec<FollowerInboxRecord> {
     query_as(
-        "SELECT users.inbox_url
+        "SELECT users.inbox_url, users.shared_inbox_url
         FROM follows
         JOIN users ON follows.follower_id = users.id
         WHERE follows.followee_id = $1
@@ -147,6 +204,25 @@ pub async fn get_follower_inboxes(state: &AppState, followee_id: i64) -> Vec<Fol
     .unwrap()
 }
 
+#[derive(sqlx::FromRow)]
+pub struct FollowerIdRecord {
+    pub follower_id: i64,
+}
+
+pub async fn get_local_follower_ids(state: &AppState, followee_id: i64) -> Vec<FollowerIdRecord> {
+    query_as(
+        "SELECT follows.follower_id
+        FROM follows
+        JOIN users ON follows.follower_id = users.id
+        WHERE follows.followee_id = $1
+        AND users.is_local = 1",
+    )
+    .bind(followee_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
 pub async fn create(state: &AppState, follower_id: i64, followee_id: i64) {
     query(
         "INSERT INTO follows (follower_id, followee_id, pending)
@@ -159,6 +235,20 @@ pub async fn create(state: &AppState, follower_id: i64, followee_id: i64) {
     .unwrap();
 }
 
+pub async fn update_ap_url(state: &AppState, follower_id: i64, followee_id: i64, ap_url: &str) {
+    query(
+        "UPDATE follows SET ap_url = $1
+        WHERE follower_id = $2
+        AND followee_id = $3",
+    )
+    .bind(ap_url)
+    .bind(follower_id)
+    .bind(followee_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
 pub async fn accept(state: &AppState, follower_id: i64, followee_id: i64) {
     query(
         "UPDATE follows SET pending = 0
@@ -184,3 +274,29 @@ pub async fn delete(state: &AppState, follower_id: i64, followee_id: i64) {
     .await
     .unwrap();
 }
+
+pub async fn decrement_counts_by_user(state: &AppState, user_id: i64) {
+    // Followees of the user
+    query(
+        "UPDATE users
+        SET follower_count = follower_count - 1
+        WHERE follower_count > 0
+        AND id IN (SELECT followee_id FROM follows WHERE follower_id = $1 AND pending = 0)",
+    )
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+
+    // Followers of the user
+    query(
+        "UPDATE users
+        SET following_count = following_count - 1
+        WHERE following_count > 0
+        AND id IN (SELECT follower_id FROM follows WHERE followee_id = $1 AND pending = 0)",
+    )
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
@@ -0,0 +1,44 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow)]
+pub struct HashtagRecord {
+    pub name: String,
+}
+
+pub async fn get_by_note(state: &AppState, note_id: i64) -> Vec<HashtagRecord> {
+    query_as(
+        "SELECT name FROM hashtags
+        WHERE note_id = $1
+        ORDER BY id ASC",
+    )
+    .bind(note_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn create(state: &AppState, note_id: i64, name: &str) {
+    query(
+        "INSERT INTO hashtags (note_id, name)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(note_id)
+    .bind(name)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn delete_by_note(state: &AppState, note_id: i64) {
+    query(
+        "DELETE FROM hashtags
+        WHERE note_id = $1",
+    )
+    .bind(note_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
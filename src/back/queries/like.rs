@@ -44,3 +44,16 @@ pub async fn delete(state: &AppState, user_id: i64, note_id: i64) {
     .await
     .unwrap();
 }
+
+pub async fn decrement_counts_by_user(state: &AppState, user_id: i64) {
+    query(
+        "UPDATE notes
+        SET like_count = like_count - 1
+        WHERE like_count > 0
+        AND id IN (SELECT note_id FROM likes WHERE user_id = $1)",
+    )
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
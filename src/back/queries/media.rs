@@ -0,0 +1,103 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow)]
+pub struct MediaRecord {
+    pub id: i64,
+    pub user_id: i64,
+    pub note_id: Option<i64>,
+    pub file_name: String,
+    pub media_type: String,
+    pub description: Option<String>,
+}
+
+pub async fn get_by_id(state: &AppState, id: i64) -> Option<MediaRecord> {
+    query_as(
+        "SELECT id, user_id, note_id, file_name, media_type, description FROM media
+        WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn get_by_file_name(state: &AppState, file_name: &str) -> Option<MediaRecord> {
+    query_as(
+        "SELECT id, user_id, note_id, file_name, media_type, description FROM media
+        WHERE file_name = $1",
+    )
+    .bind(file_name)
+    .fetch_optional(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn get_by_note(state: &AppState, note_id: i64) -> Vec<MediaRecord> {
+    query_as(
+        "SELECT id, user_id, note_id, file_name, media_type, description FROM media
+        WHERE note_id = $1
+        ORDER BY id ASC",
+    )
+    .bind(note_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn get_by_user(state: &AppState, user_id: i64) -> Vec<MediaRecord> {
+    query_as(
+        "SELECT id, user_id, note_id, file_name, media_type, description FROM media
+        WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn create(
+    state: &AppState,
+    id: i64,
+    user_id: i64,
+    file_name: &str,
+    media_type: &str,
+    description: Option<String>,
+    created_at: &str,
+) {
+    query(
+        "INSERT INTO media (id, user_id, file_name, media_type, description, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(file_name)
+    .bind(media_type)
+    .bind(description)
+    .bind(created_at)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn update_note_id(state: &AppState, id: i64, note_id: i64) {
+    query(
+        "UPDATE media
+        SET note_id = $1
+        WHERE id = $2",
+    )
+    .bind(note_id)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn delete(state: &AppState, id: i64) {
+    query("DELETE FROM media WHERE id = $1")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+}
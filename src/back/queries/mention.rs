@@ -0,0 +1,50 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow)]
+pub struct MentionRecord {
+    pub _id: i64,
+}
+
+pub async fn get(state: &AppState, note_id: i64, user_id: i64) -> Option<MentionRecord> {
+    query_as(
+        "SELECT id AS _id FROM mentions
+        WHERE note_id = $1
+        AND user_id = $2",
+    )
+    .bind(note_id)
+    .bind(user_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+#[derive(sqlx::FromRow)]
+pub struct MentionedUserRecord {
+    pub user_id: i64,
+}
+
+pub async fn get_by_note(state: &AppState, note_id: i64) -> Vec<MentionedUserRecord> {
+    query_as(
+        "SELECT user_id FROM mentions
+        WHERE note_id = $1",
+    )
+    .bind(note_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn create(state: &AppState, note_id: i64, user_id: i64) {
+    query(
+        "INSERT INTO mentions (note_id, user_id)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(note_id)
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
@@ -1,11 +1,20 @@
+pub mod attachment;
 pub mod block;
 pub mod boost;
+pub mod conversation;
+pub mod delivery;
 pub mod follow;
+pub mod hashtag;
 pub mod like;
+pub mod media;
+pub mod mention;
 pub mod note;
 pub mod notification;
 #[cfg(feature = "api")]
 pub mod oauth;
+pub mod pin;
+pub mod poll;
+pub mod profile_field;
 #[cfg(feature = "web")]
 pub mod session;
 pub mod timeline;
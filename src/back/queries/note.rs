@@ -1,4 +1,5 @@
 use crate::back::init::AppState;
+use crate::back::queries::attachment::AttachmentRecord;
 
 use sqlx::{query, query_as};
 
@@ -7,12 +8,17 @@ pub struct NoteRecord {
     pub id: i64,
     pub ap_url: String,
     pub author_id: i64,
+    pub boosted_id: Option<i64>,
     pub content: String,
-    pub attachments: Option<String>,
     pub parent_id: Option<i64>,
     pub parent_author_username: Option<String>,
+    pub quote_id: Option<i64>,
     pub created_at: String,
-    pub is_public: i64,
+    pub updated_at: Option<String>,
+    pub visibility: String,
+    pub summary: Option<String>,
+    pub is_sensitive: i64,
+    pub emojis: String,
 }
 
 pub async fn get_by_id(state: &AppState, id: i64) -> Option<NoteRecord> {
@@ -41,18 +47,27 @@ pub struct NoteWithAuthorRecord {
     pub boosted_username: Option<String>,
     pub boosted_created_at: Option<String>,
     pub content: String,
-    pub attachments: Option<String>,
+    #[sqlx(skip)]
+    pub attachments: Vec<AttachmentRecord>,
+    #[sqlx(skip)]
+    pub quote: Option<Box<NoteWithAuthorRecord>>,
     pub parent_id: Option<i64>,
     pub parent_author_username: Option<String>,
     pub created_at: String,
-    pub is_public: i64,
+    pub visibility: String,
+    pub summary: Option<String>,
+    pub is_sensitive: i64,
+    pub emojis: String,
+    pub author_emojis: String,
+    pub author_avatar_url: Option<String>,
+    pub author_header_url: Option<String>,
     pub like_count: i64,
     pub boost_count: i64,
 }
 
 pub async fn get_with_author_by_id(state: &AppState, id: i64) -> Option<NoteWithAuthorRecord> {
     query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
         WHERE n.id = $1"
@@ -66,48 +81,67 @@ pub async fn get_with_author_by_id(state: &AppState, id: i64) -> Option<NoteWith
 pub async fn get_replies_by_parent_id(
     state: &AppState,
     parent_id: i64,
+    viewer_id: i64,
 ) -> Vec<NoteWithAuthorRecord> {
-    query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+    let query_str = format!(
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
         WHERE n.parent_id = $1
-        AND n.is_public = 1
+        AND {}
         AND n.boosted_id IS NULL
         ORDER BY n.created_at ASC",
+        visible_to("$2")
+    );
+    query_as(&query_str)
+        .bind(parent_id)
+        .bind(viewer_id)
+        .fetch_all(&state.db_pool)
+        .await
+        .unwrap()
+}
+
+// Condition for notes the viewer may see (viewer 0 sees public and unlisted only)
+pub fn visible_to(viewer_id: &str) -> String {
+    format!(
+        "(n.visibility IN ('public', 'unlisted')
+        OR n.author_id = {0}
+        OR EXISTS (SELECT 1 FROM mentions AS m WHERE m.note_id = n.id AND m.user_id = {0})
+        OR (n.visibility = 'private'
+        AND EXISTS (SELECT 1 FROM follows AS vf WHERE vf.follower_id = {0} AND vf.followee_id = n.author_id AND vf.pending = 0)))",
+        viewer_id
     )
-    .bind(parent_id)
-    .fetch_all(&state.db_pool)
-    .await
-    .unwrap()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create(
     state: &AppState,
     id: i64,
     ap_url: &str,
     author_id: i64,
     content: &str,
-    attachments: Option<String>,
     parent_id: Option<i64>,
     parent_author_username: Option<String>,
     created_at: &str,
-    is_public: i64,
+    visibility: &str,
+    summary: Option<String>,
+    is_sensitive: i64,
 ) {
     for attempt in 0..10 {
         let res = query(
-            "INSERT INTO notes (id, ap_url, author_id, content, attachments, parent_id, parent_author_username, created_at, is_public)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            "INSERT INTO notes (id, ap_url, author_id, content, parent_id, parent_author_username, created_at, visibility, summary, is_sensitive)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
         )
         .bind(id + attempt)
         .bind(ap_url)
         .bind(author_id)
         .bind(content)
-        .bind(&attachments)
         .bind(parent_id)
         .bind(&parent_author_username)
         .bind(created_at)
-        .bind(is_public)
+        .bind(visibility)
+        .bind(&summary)
+        .bind(is_sensitive)
         .execute(&state.db_pool)
         .await;
 
@@ -124,6 +158,192 @@ pub async fn create(
     panic!("Failed to insert note");
 }
 
+pub async fn update_quote_id(state: &AppState, id: i64, quote_id: Option<i64>) {
+    query(
+        "UPDATE notes
+        SET quote_id = $1
+        WHERE id = $2",
+    )
+    .bind(quote_id)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn update_emojis(state: &AppState, id: i64, emojis: &str) {
+    // Also update boosts, which hold a copy of the content
+    query(
+        "UPDATE notes
+        SET emojis = $1
+        WHERE id = $2 OR boosted_id = $2",
+    )
+    .bind(emojis)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn update_conversation_id(state: &AppState, id: i64, conversation_id: i64) {
+    query(
+        "UPDATE notes
+        SET conversation_id = $1
+        WHERE id = $2",
+    )
+    .bind(conversation_id)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn update(
+    state: &AppState,
+    id: i64,
+    content: &str,
+    summary: Option<String>,
+    is_sensitive: i64,
+    updated_at: &str,
+) {
+    // Also update boosts, which hold a copy of the content
+    query(
+        "UPDATE notes
+        SET content = $1, summary = $2, is_sensitive = $3, updated_at = $4
+        WHERE id = $5 OR boosted_id = $5",
+    )
+    .bind(content)
+    .bind(summary)
+    .bind(is_sensitive)
+    .bind(updated_at)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+#[cfg(feature = "api")]
+#[derive(sqlx::FromRow)]
+pub struct NoteEditRecord {
+    pub content: String,
+    pub attachments: Option<String>,
+    pub summary: Option<String>,
+    pub is_sensitive: i64,
+    pub created_at: String,
+}
+
+#[cfg(feature = "api")]
+pub async fn get_edits(state: &AppState, note_id: i64) -> Vec<NoteEditRecord> {
+    query_as(
+        "SELECT content, attachments, summary, is_sensitive, created_at FROM note_edits
+        WHERE note_id = $1
+        ORDER BY created_at ASC, id ASC",
+    )
+    .bind(note_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn create_edit(
+    state: &AppState,
+    note_id: i64,
+    content: &str,
+    attachments: Option<String>,
+    summary: Option<String>,
+    is_sensitive: i64,
+    created_at: &str,
+) {
+    query(
+        "INSERT INTO note_edits (note_id, content, attachments, summary, is_sensitive, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(note_id)
+    .bind(content)
+    .bind(attachments)
+    .bind(summary)
+    .bind(is_sensitive)
+    .bind(created_at)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+#[derive(sqlx::FromRow)]
+pub struct LegacyAttachmentsRecord {
+    pub id: i64,
+    pub note_id: i64,
+    pub attachments: String,
+}
+
+pub async fn get_legacy_attachments(state: &AppState) -> Vec<LegacyAttachmentsRecord> {
+    query_as(
+        "SELECT id, id AS note_id, attachments FROM notes
+        WHERE attachments IS NOT NULL",
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn clear_legacy_attachments(state: &AppState, id: i64) {
+    query(
+        "UPDATE notes
+        SET attachments = NULL
+        WHERE id = $1",
+    )
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+// Edits written before attachments were stored as JSON
+pub async fn get_legacy_edit_attachments(state: &AppState) -> Vec<LegacyAttachmentsRecord> {
+    query_as(
+        "SELECT id, note_id, attachments FROM note_edits
+        WHERE attachments IS NOT NULL AND attachments NOT LIKE '[%'",
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn update_edit_attachments(state: &AppState, id: i64, attachments: &str) {
+    query(
+        "UPDATE note_edits
+        SET attachments = $1
+        WHERE id = $2",
+    )
+    .bind(attachments)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+// Replace is_public from before visibility, None when already migrated
+pub async fn migrate_is_public(state: &AppState) -> Option<u64> {
+    // Fails when the column already exists
+    let _ = query("ALTER TABLE notes ADD COLUMN visibility TEXT NOT NULL DEFAULT 'public'")
+        .execute(&state.db_pool)
+        .await;
+    let res = query(
+        "UPDATE notes
+        SET visibility = 'unlisted'
+        WHERE is_public = 0",
+    )
+    .execute(&state.db_pool)
+    .await
+    .ok()?;
+    query("ALTER TABLE notes DROP COLUMN is_public")
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+
+    Some(res.rows_affected())
+}
+
 pub async fn increment_like_count(state: &AppState, id: i64) {
     query(
         "UPDATE notes
@@ -172,6 +392,19 @@ pub async fn decrement_boost_count(state: &AppState, id: i64) {
     .unwrap();
 }
 
+pub async fn decrement_boost_counts_by_author(state: &AppState, author_id: i64) {
+    query(
+        "UPDATE notes
+        SET boost_count = boost_count - 1
+        WHERE boost_count > 0
+        AND id IN (SELECT boosted_id FROM notes WHERE author_id = $1 AND boosted_id IS NOT NULL)",
+    )
+    .bind(author_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
 pub async fn delete(state: &AppState, id: i64) {
     query(
         "DELETE FROM notes
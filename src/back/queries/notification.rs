@@ -34,12 +34,17 @@ pub async fn get(state: &AppState, recipient_id: i64, limit: i64) -> Vec<Notific
 pub struct NotificationWithNoteRecord {
     pub display_name: String,
     pub username: String,
+    pub avatar_url: Option<String>,
+    pub header_url: Option<String>,
     pub event_type: i64,
     pub note_id: Option<i64>,
     pub content: Option<String>,
-    pub attachments: Option<String>,
     pub note_created_at: Option<String>,
     pub parent_id: Option<i64>,
+    pub visibility: Option<String>,
+    pub summary: Option<String>,
+    pub is_sensitive: Option<i64>,
+    pub emojis: Option<String>,
     pub like_count: Option<i64>,
     pub boost_count: Option<i64>,
     pub created_at: String,
@@ -53,7 +58,7 @@ pub async fn get_with_note(
     limit: i64,
 ) -> Vec<NotificationWithNoteRecord> {
     query_as(
-        "SELECT u.display_name, u.username, notif.event_type, notif.note_id, note.content, note.attachments, note.created_at AS note_created_at, note.parent_id, note.parent_author_username, note.like_count, note.boost_count, notif.created_at
+        "SELECT u.display_name, u.username, u.avatar_url, u.header_url, notif.event_type, notif.note_id, note.content, note.created_at AS note_created_at, note.parent_id, note.parent_author_username, note.visibility, note.summary, note.is_sensitive, note.emojis, note.like_count, note.boost_count, notif.created_at
         FROM notifications AS notif
         JOIN users AS u ON notif.sender_id = u.id
         LEFT JOIN notes AS note ON notif.note_id = note.id
@@ -0,0 +1,73 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow)]
+pub struct PinRecord {
+    pub _id: i64,
+}
+
+pub async fn get(state: &AppState, user_id: i64, note_id: i64) -> Option<PinRecord> {
+    query_as(
+        "SELECT id AS _id FROM pins
+        WHERE user_id = $1
+        AND note_id = $2",
+    )
+    .bind(user_id)
+    .bind(note_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+#[derive(sqlx::FromRow)]
+pub struct CountRecord {
+    pub count: i64,
+}
+
+pub async fn count(state: &AppState, user_id: i64) -> i64 {
+    query_as::<_, CountRecord>("SELECT COUNT(*) AS count FROM pins WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap()
+        .count
+}
+
+pub async fn create(state: &AppState, user_id: i64, note_id: i64, created_at: &str) {
+    query(
+        "INSERT INTO pins (user_id, note_id, created_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(note_id)
+    .bind(created_at)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn delete(state: &AppState, user_id: i64, note_id: i64) {
+    query(
+        "DELETE FROM pins
+        WHERE user_id = $1
+        AND note_id = $2",
+    )
+    .bind(user_id)
+    .bind(note_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn delete_by_user(state: &AppState, user_id: i64) {
+    query(
+        "DELETE FROM pins
+        WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
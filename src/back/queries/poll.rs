@@ -0,0 +1,177 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow)]
+pub struct PollRecord {
+    pub id: i64,
+    pub note_id: i64,
+    pub is_multiple: i64,
+    pub expires_at: Option<String>,
+    pub voters_count: i64,
+}
+
+pub async fn get_by_note(state: &AppState, note_id: i64) -> Option<PollRecord> {
+    query_as(
+        "SELECT id, note_id, is_multiple, expires_at, voters_count FROM polls
+        WHERE note_id = $1",
+    )
+    .bind(note_id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+#[cfg(feature = "api")]
+pub async fn get_by_id(state: &AppState, id: i64) -> Option<PollRecord> {
+    query_as(
+        "SELECT id, note_id, is_multiple, expires_at, voters_count FROM polls
+        WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn upsert(
+    state: &AppState,
+    note_id: i64,
+    is_multiple: i64,
+    expires_at: Option<String>,
+    voters_count: i64,
+) {
+    query(
+        "INSERT INTO polls (note_id, is_multiple, expires_at, voters_count)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (note_id) DO UPDATE
+        SET is_multiple = excluded.is_multiple,
+            expires_at = excluded.expires_at,
+            voters_count = excluded.voters_count",
+    )
+    .bind(note_id)
+    .bind(is_multiple)
+    .bind(expires_at)
+    .bind(voters_count)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn increment_voters_count(state: &AppState, id: i64) {
+    query(
+        "UPDATE polls
+        SET voters_count = voters_count + 1
+        WHERE id = $1",
+    )
+    .bind(id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+#[derive(sqlx::FromRow)]
+pub struct PollOptionRecord {
+    pub position: i64,
+    pub title: String,
+    pub votes_count: i64,
+}
+
+pub async fn get_options(state: &AppState, poll_id: i64) -> Vec<PollOptionRecord> {
+    query_as(
+        "SELECT position, title, votes_count FROM poll_options
+        WHERE poll_id = $1
+        ORDER BY position ASC",
+    )
+    .bind(poll_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn upsert_option(
+    state: &AppState,
+    poll_id: i64,
+    position: i64,
+    title: &str,
+    votes_count: i64,
+) {
+    query(
+        "INSERT INTO poll_options (poll_id, position, title, votes_count)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (poll_id, position) DO UPDATE
+        SET title = excluded.title,
+            votes_count = excluded.votes_count",
+    )
+    .bind(poll_id)
+    .bind(position)
+    .bind(title)
+    .bind(votes_count)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn delete_options_from(state: &AppState, poll_id: i64, position: i64) {
+    query(
+        "DELETE FROM poll_options
+        WHERE poll_id = $1 AND position >= $2",
+    )
+    .bind(poll_id)
+    .bind(position)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn increment_votes_count(state: &AppState, poll_id: i64, position: i64) {
+    query(
+        "UPDATE poll_options
+        SET votes_count = votes_count + 1
+        WHERE poll_id = $1 AND position = $2",
+    )
+    .bind(poll_id)
+    .bind(position)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+#[derive(sqlx::FromRow)]
+pub struct PollVoteRecord {
+    pub position: i64,
+}
+
+pub async fn get_votes(state: &AppState, poll_id: i64, user_id: i64) -> Vec<PollVoteRecord> {
+    query_as(
+        "SELECT position FROM poll_votes
+        WHERE poll_id = $1 AND user_id = $2
+        ORDER BY position ASC",
+    )
+    .bind(poll_id)
+    .bind(user_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn create_vote(
+    state: &AppState,
+    poll_id: i64,
+    user_id: i64,
+    position: i64,
+    created_at: &str,
+) {
+    query(
+        "INSERT INTO poll_votes (poll_id, user_id, position, created_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(poll_id)
+    .bind(user_id)
+    .bind(position)
+    .bind(created_at)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
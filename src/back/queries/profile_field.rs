@@ -0,0 +1,45 @@
+use crate::back::init::AppState;
+
+use sqlx::{query, query_as};
+
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct ProfileFieldRecord {
+    pub name: String,
+    pub value: String,
+}
+
+pub async fn get_by_user(state: &AppState, user_id: i64) -> Vec<ProfileFieldRecord> {
+    query_as(
+        "SELECT name, value FROM profile_fields
+        WHERE user_id = $1
+        ORDER BY id ASC",
+    )
+    .bind(user_id)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn create(state: &AppState, user_id: i64, name: &str, value: &str) {
+    query(
+        "INSERT INTO profile_fields (user_id, name, value)
+        VALUES ($1, $2, $3)",
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(value)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn delete_by_user(state: &AppState, user_id: i64) {
+    query(
+        "DELETE FROM profile_fields
+        WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
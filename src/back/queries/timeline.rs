@@ -1,33 +1,61 @@
 use crate::back::init::AppState;
-use crate::back::queries::note::NoteWithAuthorRecord;
+use crate::back::queries::note::{NoteWithAuthorRecord, visible_to};
 
 use sqlx::query_as;
 
 pub async fn get_user(
     state: &AppState,
     user_id: i64,
+    viewer_id: i64,
     until_date: &str,
     until_id: i64,
     limit: i64,
 ) -> Vec<NoteWithAuthorRecord> {
-    query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+    let query_str = format!(
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
         WHERE ((n.created_at < $1)
         OR (n.created_at = $1 AND n.id < $2))
         AND u.id = $3
-        AND n.is_public = 1
+        AND {}
         ORDER BY n.created_at DESC, n.id DESC
         LIMIT $4",
-    )
-    .bind(until_date)
-    .bind(until_id)
-    .bind(user_id)
-    .bind(limit)
-    .fetch_all(&state.db_pool)
-    .await
-    .unwrap()
+        visible_to("$5")
+    );
+    query_as(&query_str)
+        .bind(until_date)
+        .bind(until_id)
+        .bind(user_id)
+        .bind(limit)
+        .bind(viewer_id)
+        .fetch_all(&state.db_pool)
+        .await
+        .unwrap()
+}
+
+// Newest pin first
+pub async fn get_pinned(
+    state: &AppState,
+    user_id: i64,
+    viewer_id: i64,
+) -> Vec<NoteWithAuthorRecord> {
+    let query_str = format!(
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
+        FROM pins AS p
+        JOIN notes AS n ON p.note_id = n.id
+        JOIN users AS u ON n.author_id = u.id
+        WHERE p.user_id = $1
+        AND {}
+        ORDER BY p.created_at DESC, p.id DESC",
+        visible_to("$2")
+    );
+    query_as(&query_str)
+        .bind(user_id)
+        .bind(viewer_id)
+        .fetch_all(&state.db_pool)
+        .await
+        .unwrap()
 }
 
 pub async fn get_home(
@@ -37,8 +65,8 @@ pub async fn get_home(
     until_id: i64,
     limit: i64,
 ) -> Vec<NoteWithAuthorRecord> {
-    query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+    let query_str = format!(
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
         LEFT JOIN follows AS f ON f.followee_id = u.id
@@ -46,16 +74,19 @@ pub async fn get_home(
         WHERE ((n.created_at < $2)
         OR (n.created_at = $2 AND n.id < $3))
         AND (f.follower_id = $1 OR u.id = $1)
+        AND {}
         ORDER BY n.created_at DESC, n.id DESC
         LIMIT $4",
-    )
-    .bind(user_id)
-    .bind(until_date)
-    .bind(until_id)
-    .bind(limit)
-    .fetch_all(&state.db_pool)
-    .await
-    .unwrap()
+        visible_to("$1")
+    );
+    query_as(&query_str)
+        .bind(user_id)
+        .bind(until_date)
+        .bind(until_id)
+        .bind(limit)
+        .fetch_all(&state.db_pool)
+        .await
+        .unwrap()
 }
 
 pub async fn get_local(
@@ -65,13 +96,13 @@ pub async fn get_local(
     limit: i64,
 ) -> Vec<NoteWithAuthorRecord> {
     query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
         WHERE ((n.created_at < $1)
         OR (n.created_at = $1 AND n.id < $2))
         AND u.is_local = 1
-        AND n.is_public = 1
+        AND n.visibility = 'public'
         ORDER BY n.created_at DESC, n.id DESC
         LIMIT $3",
     )
@@ -90,12 +121,12 @@ pub async fn get_federated(
     limit: i64,
 ) -> Vec<NoteWithAuthorRecord> {
     query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
         WHERE ((n.created_at < $1)
         OR (n.created_at = $1 AND n.id < $2))
-        AND n.is_public = 1
+        AND n.visibility = 'public'
         ORDER BY n.created_at DESC, n.id DESC
         LIMIT $3",
     )
@@ -115,8 +146,8 @@ pub async fn get_home_since(
     since_id: i64,
     limit: i64,
 ) -> Vec<NoteWithAuthorRecord> {
-    query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+    let query_str = format!(
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
         LEFT JOIN follows AS f ON f.followee_id = u.id
@@ -124,10 +155,39 @@ pub async fn get_home_since(
         WHERE ((n.created_at > $2)
         OR (n.created_at = $2 AND n.id > $3))
         AND (f.follower_id = $1 OR u.id = $1)
+        AND {}
         ORDER BY n.created_at DESC, n.id DESC
         LIMIT $4",
+        visible_to("$1")
+    );
+    query_as(&query_str)
+        .bind(user_id)
+        .bind(since_date)
+        .bind(since_id)
+        .bind(limit)
+        .fetch_all(&state.db_pool)
+        .await
+        .unwrap()
+}
+
+#[cfg(feature = "api")]
+pub async fn get_local_since(
+    state: &AppState,
+    since_date: &str,
+    since_id: i64,
+    limit: i64,
+) -> Vec<NoteWithAuthorRecord> {
+    query_as(
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
+        FROM notes AS n
+        JOIN users AS u ON n.author_id = u.id
+        WHERE ((n.created_at > $1)
+        OR (n.created_at = $1 AND n.id > $2))
+        AND u.is_local = 1
+        AND n.visibility = 'public'
+        ORDER BY n.created_at DESC, n.id DESC
+        LIMIT $3",
     )
-    .bind(user_id)
     .bind(since_date)
     .bind(since_id)
     .bind(limit)
@@ -137,20 +197,19 @@ pub async fn get_home_since(
 }
 
 #[cfg(feature = "api")]
-pub async fn get_local_since(
+pub async fn get_federated_since(
     state: &AppState,
     since_date: &str,
     since_id: i64,
     limit: i64,
 ) -> Vec<NoteWithAuthorRecord> {
     query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
         WHERE ((n.created_at > $1)
         OR (n.created_at = $1 AND n.id > $2))
-        AND u.is_local = 1
-        AND n.is_public = 1
+        AND n.visibility = 'public'
         ORDER BY n.created_at DESC, n.id DESC
         LIMIT $3",
     )
@@ -162,25 +221,77 @@ pub async fn get_local_since(
     .unwrap()
 }
 
+pub async fn get_conversation(
+    state: &AppState,
+    conversation_id: i64,
+    limit: i64,
+) -> Vec<NoteWithAuthorRecord> {
+    query_as(
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
+        FROM notes AS n
+        JOIN users AS u ON n.author_id = u.id
+        WHERE n.conversation_id = $1
+        ORDER BY n.created_at DESC, n.id DESC
+        LIMIT $2",
+    )
+    .bind(conversation_id)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
+pub async fn get_tag(
+    state: &AppState,
+    hashtag: &str,
+    until_date: &str,
+    until_id: i64,
+    limit: i64,
+) -> Vec<NoteWithAuthorRecord> {
+    query_as(
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
+        FROM notes AS n
+        JOIN users AS u ON n.author_id = u.id
+        JOIN hashtags AS h ON h.note_id = n.id
+        WHERE ((n.created_at < $1)
+        OR (n.created_at = $1 AND n.id < $2))
+        AND h.name = $3
+        AND n.visibility = 'public'
+        ORDER BY n.created_at DESC, n.id DESC
+        LIMIT $4",
+    )
+    .bind(until_date)
+    .bind(until_id)
+    .bind(hashtag)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await
+    .unwrap()
+}
+
 #[cfg(feature = "api")]
-pub async fn get_federated_since(
+pub async fn get_tag_since(
     state: &AppState,
+    hashtag: &str,
     since_date: &str,
     since_id: i64,
     limit: i64,
 ) -> Vec<NoteWithAuthorRecord> {
     query_as(
-        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.attachments, n.parent_id, n.parent_author_username, n.created_at, n.is_public, n.like_count, n.boost_count
+        "SELECT n.author_id, u.display_name, u.username, n.id, n.boosted_id, n.boosted_username, n.boosted_created_at, n.content, n.parent_id, n.parent_author_username, n.created_at, n.visibility, n.summary, n.is_sensitive, n.emojis, u.emojis AS author_emojis, u.avatar_url AS author_avatar_url, u.header_url AS author_header_url, n.like_count, n.boost_count
         FROM notes AS n
         JOIN users AS u ON n.author_id = u.id
+        JOIN hashtags AS h ON h.note_id = n.id
         WHERE ((n.created_at > $1)
         OR (n.created_at = $1 AND n.id > $2))
-        AND n.is_public = 1
+        AND h.name = $3
+        AND n.visibility = 'public'
         ORDER BY n.created_at DESC, n.id DESC
-        LIMIT $3",
+        LIMIT $4",
     )
     .bind(since_date)
     .bind(since_id)
+    .bind(hashtag)
     .bind(limit)
     .fetch_all(&state.db_pool)
     .await
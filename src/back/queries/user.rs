@@ -9,13 +9,21 @@ pub struct UserRecord {
     pub password_hash: Option<String>,
     pub ap_url: String,
     pub inbox_url: String,
+    pub shared_inbox_url: Option<String>,
     pub private_key: Option<String>,
     pub public_key: Option<String>,
+    pub key_id: Option<String>,
+    pub also_known_as: String,
+    pub moved_to: Option<String>,
+    pub emojis: String,
+    pub avatar_url: Option<String>,
+    pub header_url: Option<String>,
     pub display_name: String,
     pub bio: String,
     pub created_at: String,
     pub updated_at: String,
     pub is_local: i64,
+    pub is_locked: i64,
     pub note_count: i64,
     pub following_count: i64,
     pub follower_count: i64,
@@ -41,6 +49,22 @@ pub async fn get_by_username_in(state: &AppState, usernames: &Vec<String>) -> Ve
     query.fetch_all(&state.db_pool).await.unwrap()
 }
 
+pub async fn get_by_id_in(state: &AppState, ids: &[i64]) -> Vec<UserRecord> {
+    if ids.is_empty() {
+        return vec![];
+    }
+    let in_placeholder = (0..ids.len())
+        .map(|id| format!("${}", id + 1))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let query_str = format!("SELECT * FROM users WHERE id IN ({})", in_placeholder);
+    let mut query = query_as::<_, UserRecord>(&query_str);
+    for id in ids {
+        query = query.bind(id);
+    }
+    query.fetch_all(&state.db_pool).await.unwrap()
+}
+
 pub async fn get_by_ap_url(state: &AppState, ap_url: &str) -> Option<UserRecord> {
     query_as("SELECT * FROM users WHERE ap_url = $1")
         .bind(ap_url)
@@ -49,6 +73,14 @@ pub async fn get_by_ap_url(state: &AppState, ap_url: &str) -> Option<UserRecord>
         .unwrap()
 }
 
+pub async fn get_by_key_id(state: &AppState, key_id: &str) -> Option<UserRecord> {
+    query_as("SELECT * FROM users WHERE key_id = $1")
+        .bind(key_id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .unwrap()
+}
+
 pub async fn get_by_id(state: &AppState, id: i64) -> UserRecord {
     query_as("SELECT * FROM users WHERE id = $1")
         .bind(id)
@@ -57,14 +89,17 @@ pub async fn get_by_id(state: &AppState, id: i64) -> UserRecord {
         .unwrap()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create(
     state: &AppState,
     username: &str,
     password_hash: Option<&str>,
     ap_url: &str,
     inbox_url: &str,
+    shared_inbox_url: Option<&str>,
     private_key: Option<&str>,
     public_key: Option<&str>,
+    key_id: Option<&str>,
     display_name: &str,
     bio: &str,
     created_at: &str,
@@ -72,15 +107,17 @@ pub async fn create(
     is_local: i64,
 ) {
     query(
-        "INSERT INTO users (username, password_hash, ap_url, inbox_url, private_key, public_key, display_name, bio, created_at, updated_at, is_local)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"
+        "INSERT INTO users (username, password_hash, ap_url, inbox_url, shared_inbox_url, private_key, public_key, key_id, display_name, bio, created_at, updated_at, is_local)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)"
     )
     .bind(username)
     .bind(password_hash)
     .bind(ap_url)
     .bind(inbox_url)
+    .bind(shared_inbox_url)
     .bind(private_key)
     .bind(public_key)
+    .bind(key_id)
     .bind(display_name)
     .bind(bio)
     .bind(created_at)
@@ -91,6 +128,19 @@ pub async fn create(
     .unwrap();
 }
 
+#[derive(sqlx::FromRow)]
+pub struct InboxRecord {
+    pub inbox_url: String,
+    pub shared_inbox_url: Option<String>,
+}
+
+pub async fn get_remote_inboxes(state: &AppState) -> Vec<InboxRecord> {
+    query_as("SELECT inbox_url, shared_inbox_url FROM users WHERE is_local = 0")
+        .fetch_all(&state.db_pool)
+        .await
+        .unwrap()
+}
+
 #[derive(sqlx::FromRow)]
 pub struct TempSignUserRecord {
     pub ap_url: String,
@@ -113,6 +163,99 @@ pub async fn update_profile(state: &AppState, user_id: i64, display_name: &str,
         .unwrap();
 }
 
+pub async fn update_avatar(state: &AppState, user_id: i64, avatar_url: Option<&str>) {
+    query("UPDATE users SET avatar_url = $1 WHERE id = $2")
+        .bind(avatar_url)
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+}
+
+pub async fn update_header(state: &AppState, user_id: i64, header_url: Option<&str>) {
+    query("UPDATE users SET header_url = $1 WHERE id = $2")
+        .bind(header_url)
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+}
+
+pub async fn update_locked(state: &AppState, user_id: i64, is_locked: i64) {
+    query("UPDATE users SET is_locked = $1 WHERE id = $2")
+        .bind(is_locked)
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+}
+
+pub async fn update_public_key(state: &AppState, user_id: i64, key_id: &str, public_key: &str) {
+    query("UPDATE users SET key_id = $1, public_key = $2 WHERE id = $3")
+        .bind(key_id)
+        .bind(public_key)
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+}
+
+// Everything a remote actor document can change
+#[allow(clippy::too_many_arguments)]
+pub async fn update_remote(
+    state: &AppState,
+    user_id: i64,
+    display_name: &str,
+    bio: &str,
+    inbox_url: &str,
+    shared_inbox_url: Option<&str>,
+    also_known_as: &str,
+    moved_to: Option<&str>,
+    is_locked: i64,
+    emojis: &str,
+    avatar_url: Option<&str>,
+    header_url: Option<&str>,
+) {
+    query(
+        "UPDATE users
+        SET display_name = $1, bio = $2, inbox_url = $3, shared_inbox_url = $4, also_known_as = $5,
+            moved_to = $6, is_locked = $7, emojis = $8, avatar_url = $9, header_url = $10
+        WHERE id = $11",
+    )
+    .bind(display_name)
+    .bind(bio)
+    .bind(inbox_url)
+    .bind(shared_inbox_url)
+    .bind(also_known_as)
+    .bind(moved_to)
+    .bind(is_locked)
+    .bind(emojis)
+    .bind(avatar_url)
+    .bind(header_url)
+    .bind(user_id)
+    .execute(&state.db_pool)
+    .await
+    .unwrap();
+}
+
+pub async fn update_aliases(state: &AppState, user_id: i64, also_known_as: &str) {
+    query("UPDATE users SET also_known_as = $1 WHERE id = $2")
+        .bind(also_known_as)
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+}
+
+pub async fn update_moved_to(state: &AppState, user_id: i64, moved_to: Option<&str>) {
+    query("UPDATE users SET moved_to = $1 WHERE id = $2")
+        .bind(moved_to)
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+}
+
 pub async fn update_password(state: &AppState, user_id: i64, password_hash: &str) {
     query("UPDATE users SET password_hash = $1 WHERE id = $2")
         .bind(password_hash)
@@ -181,6 +324,14 @@ pub async fn decrement_follower_count(state: &AppState, id: i64) {
     .unwrap();
 }
 
+pub async fn delete(state: &AppState, id: i64) {
+    query("DELETE FROM users WHERE id = $1")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
+}
+
 #[derive(sqlx::FromRow)]
 pub struct CountRecord {
     pub count: i64,
@@ -0,0 +1,203 @@
+use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::queries;
+use crate::back::queries::note::NoteWithAuthorRecord;
+
+use serde_json::{Value, json};
+
+// FEP-e232 object links
+const LINK_MEDIA_TYPE: &str =
+    r#"application/ld+json; profile="https://www.w3.org/ns/activitystreams""#;
+
+// quoteUrl, _misskey_quote and quoteUri are aliases, FEP-e232 links are tags
+pub fn parse_from_json(note_json: &Value) -> Option<String> {
+    for key in ["quoteUrl", "_misskey_quote", "quoteUri", "quote"] {
+        let quote_url = match &note_json[key] {
+            Value::String(url) => Some(url.as_str()),
+            object => object["id"].as_str(),
+        };
+        if let Some(quote_url) = quote_url {
+            return Some(quote_url.to_string());
+        }
+    }
+
+    let tags = match &note_json["tag"] {
+        Value::Array(tags) => tags.iter().collect(),
+        Value::Object(_) => vec![&note_json["tag"]],
+        _ => vec![],
+    };
+    tags.into_iter()
+        .find(|tag| {
+            tag["type"] == "Link"
+                && (tag["mediaType"] == LINK_MEDIA_TYPE
+                    || tag["mediaType"] == "application/activity+json")
+        })
+        .and_then(|tag| tag["href"].as_str())
+        .map(|href| href.to_string())
+}
+
+// Remove the "RE: url" line other servers append for clients without quote support
+pub fn strip_fallback(content: &str) -> String {
+    let content = content.trim_end();
+    if let Some((rest, last_line)) = content.rsplit_once('\n')
+        && let Some(url) = last_line.trim().strip_prefix("RE: ")
+        && !url.contains(char::is_whitespace)
+        && !rest.trim().is_empty()
+    {
+        return rest.trim_end().to_string();
+    }
+    content.to_string()
+}
+
+// Only public and unlisted notes can be quoted
+pub async fn validate(state: &AppState, quote_id: i64) -> Result<(), String> {
+    let Some(quoted) = queries::note::get_by_id(state, quote_id).await else {
+        return Err("Quoted note not found".to_string());
+    };
+    if quoted.boosted_id.is_some() {
+        return Err("Cannot quote a boost".to_string());
+    }
+    if quoted.visibility != "public" && quoted.visibility != "unlisted" {
+        return Err("Only public notes can be quoted".to_string());
+    }
+
+    Ok(())
+}
+
+pub async fn add(state: &AppState, note_id: i64, quote_id: i64) -> Result<(), String> {
+    validate(state, quote_id).await?;
+    queries::note::update_quote_id(state, note_id, Some(quote_id)).await;
+
+    Ok(())
+}
+
+// Fetch the quoted note if needed and store the reference
+pub async fn add_remote(state: &AppState, note_id: i64, quote_url: &str, depth: u32) {
+    let quote_id = if let Some(quoted) = queries::note::get_by_ap_url(state, quote_url).await {
+        quoted.id
+    } else {
+        match note::add_remote(state, quote_url, depth + 1).await {
+            Ok(quote_id) => quote_id,
+            Err(e) => {
+                println!("Failed to fetch quoted note {}: {}", quote_url, e);
+                return;
+            }
+        }
+    };
+    if quote_id != note_id {
+        queries::note::update_quote_id(state, note_id, Some(quote_id)).await;
+    }
+}
+
+// Boosts show the quote of the boosted note
+pub async fn fill(state: &AppState, notes: &mut [NoteWithAuthorRecord]) {
+    for note in notes {
+        fill_one(state, note).await;
+    }
+}
+
+pub async fn fill_one(state: &AppState, note: &mut NoteWithAuthorRecord) {
+    note.quote = get(state, note.boosted_id.unwrap_or(note.id))
+        .await
+        .map(Box::new);
+}
+
+// Quoted note with attachments, hidden if it is no longer public
+pub async fn get(state: &AppState, note_id: i64) -> Option<NoteWithAuthorRecord> {
+    let quote_id = queries::note::get_by_id(state, note_id).await?.quote_id?;
+    get_quotable(state, quote_id).await
+}
+
+pub async fn get_quotable(state: &AppState, quote_id: i64) -> Option<NoteWithAuthorRecord> {
+    let mut quoted = queries::note::get_with_author_by_id(state, quote_id)
+        .await
+        .filter(|quoted| quoted.visibility == "public" || quoted.visibility == "unlisted")?;
+    quoted.attachments = queries::attachment::get_by_note(state, quoted.id).await;
+    Some(quoted)
+}
+
+// Add quote fields and a fallback link to a Note object
+pub async fn add_quote_fields(state: &AppState, note_id: i64, note_object: &mut Value) {
+    let Some(quote_id) = queries::note::get_by_id(state, note_id)
+        .await
+        .and_then(|note| note.quote_id)
+    else {
+        return;
+    };
+    let Some(quoted) = queries::note::get_by_id(state, quote_id).await else {
+        return;
+    };
+
+    note_object["quoteUrl"] = json!(quoted.ap_url);
+    note_object["_misskey_quote"] = json!(quoted.ap_url);
+    note_object["quoteUri"] = json!(quoted.ap_url);
+
+    let link = json!({
+        "type": "Link",
+        "mediaType": LINK_MEDIA_TYPE,
+        "href": quoted.ap_url,
+        "name": format!("RE: {}", quoted.ap_url),
+    });
+    match &mut note_object["tag"] {
+        Value::Array(tags) => tags.push(link),
+        tag => *tag = json!([link]),
+    }
+
+    let content = note_object["content"].as_str().unwrap_or("");
+    note_object["content"] = json!(format!(
+        r#"{}<span class="quote-inline"><br><br>RE: <a href="{}">{}</a></span>"#,
+        content,
+        tera::escape_html(&quoted.ap_url),
+        tera::escape_html(&quoted.ap_url)
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_fallback_removes_trailing_re_line() {
+        assert_eq!(
+            strip_fallback("<p>nice</p>\nRE: https://r.example/notes/1\n"),
+            "<p>nice</p>"
+        );
+    }
+
+    #[test]
+    fn strip_fallback_keeps_other_content() {
+        assert_eq!(
+            strip_fallback("RE: https://r.example/notes/1"),
+            "RE: https://r.example/notes/1"
+        );
+        assert_eq!(strip_fallback("hi\nRE: not a url"), "hi\nRE: not a url");
+        assert_eq!(strip_fallback("hi\nthere"), "hi\nthere");
+    }
+
+    #[test]
+    fn parse_from_json_reads_aliases_and_links() {
+        let url = "https://r.example/notes/1";
+        assert_eq!(
+            parse_from_json(&json!({"quoteUrl": url})).as_deref(),
+            Some(url)
+        );
+        assert_eq!(
+            parse_from_json(&json!({"_misskey_quote": url})).as_deref(),
+            Some(url)
+        );
+        assert_eq!(
+            parse_from_json(&json!({"quote": {"id": url}})).as_deref(),
+            Some(url)
+        );
+
+        let tag = json!({"type": "Link", "mediaType": LINK_MEDIA_TYPE, "href": url});
+        assert_eq!(
+            parse_from_json(&json!({"tag": [tag]})).as_deref(),
+            Some(url)
+        );
+        assert_eq!(parse_from_json(&json!({"tag": tag})).as_deref(), Some(url));
+
+        let mention = json!({"type": "Mention", "href": url});
+        assert_eq!(parse_from_json(&json!({"tag": [mention]})), None);
+    }
+}
@@ -36,7 +36,7 @@ pub async fn search(state: &AppState, q: &str) -> Result<String, String> {
     }
 }
 
-async fn resolve_acct(state: &AppState, acct: &str) -> Option<String> {
+pub async fn resolve_acct(state: &AppState, acct: &str) -> Option<String> {
     let parts: Vec<&str> = acct.split("@").collect();
     if parts.len() != 2 {
         return None;
@@ -1,5 +1,10 @@
+use crate::back::delivery;
+use crate::back::follow;
 use crate::back::init::AppState;
+use crate::back::media;
 use crate::back::queries;
+use crate::back::queries::user::UserRecord;
+use crate::back::search;
 use crate::back::utils;
 
 use argon2::{
@@ -8,9 +13,12 @@ use argon2::{
 };
 use rand::rngs::OsRng;
 use rsa::{RsaPrivateKey, RsaPublicKey, pkcs8::EncodePrivateKey, pkcs8::EncodePublicKey};
-use serde_json::Value;
+use serde_json::{Value, json};
 use url::Url;
 
+pub const MAX_FIELDS: usize = 4;
+pub const MAX_FIELD_CHARS: usize = 255;
+
 pub async fn add(state: &AppState, username: &str, password: &str) -> Result<(), String> {
     // Validation
     if username.is_empty() || password.is_empty() {
@@ -73,8 +81,10 @@ pub async fn add(state: &AppState, username: &str, password: &str) -> Result<(),
         Some(&password_hash),
         &ap_url,
         &inbox_url,
+        None,
         Some(&private_key_pem),
         Some(&public_key_pem),
+        None,
         username,
         "",
         &created_at,
@@ -92,6 +102,74 @@ pub async fn update_profile(state: &AppState, user_id: i64, display_name: &str,
     queries::user::update_profile(state, user_id, display_name, &bio).await;
 }
 
+// Fields are given as raw name and value pairs
+pub async fn update_fields(
+    state: &AppState,
+    user_id: i64,
+    fields: &[(String, String)],
+) -> Result<(), String> {
+    let fields = fields
+        .iter()
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .filter(|(name, value)| !name.is_empty() || !value.is_empty())
+        .collect::<Vec<(&str, &str)>>();
+    if fields.len() > MAX_FIELDS {
+        return Err(format!("At most {} profile fields allowed", MAX_FIELDS));
+    }
+    if fields.iter().any(|(name, value)| {
+        name.chars().count() > MAX_FIELD_CHARS || value.chars().count() > MAX_FIELD_CHARS
+    }) {
+        return Err(format!(
+            "Profile fields must be at most {} characters",
+            MAX_FIELD_CHARS
+        ));
+    }
+
+    let fields = fields
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), utils::parse_content(state, value)))
+        .collect::<Vec<(String, String)>>();
+    replace_fields(state, user_id, &fields).await;
+    Ok(())
+}
+
+async fn replace_fields(state: &AppState, user_id: i64, fields: &[(String, String)]) {
+    queries::profile_field::delete_by_user(state, user_id).await;
+    for (name, value) in fields {
+        queries::profile_field::create(state, user_id, name, value).await;
+    }
+}
+
+pub async fn update_avatar(
+    state: &AppState,
+    user_id: i64,
+    media_type: &str,
+    data: &[u8],
+) -> Result<(), String> {
+    let user = queries::user::get_by_id(state, user_id).await;
+    let avatar_url = media::add_profile_image(state, user_id, media_type, data).await?;
+    queries::user::update_avatar(state, user_id, Some(&avatar_url)).await;
+    if let Some(old_url) = user.avatar_url {
+        media::delete_profile_image(state, &old_url).await;
+    }
+    Ok(())
+}
+
+pub async fn update_header(
+    state: &AppState,
+    user_id: i64,
+    media_type: &str,
+    data: &[u8],
+) -> Result<(), String> {
+    let user = queries::user::get_by_id(state, user_id).await;
+    let header_url = media::add_profile_image(state, user_id, media_type, data).await?;
+    queries::user::update_header(state, user_id, Some(&header_url)).await;
+    if let Some(old_url) = user.header_url {
+        media::delete_profile_image(state, &old_url).await;
+    }
+    Ok(())
+}
+
 pub async fn update_password(state: &AppState, user_id: i64, password: &str) {
     // Hash password
     let argon2 = Argon2::default();
@@ -142,10 +220,46 @@ pub async fn verify_password(state: &AppState, username: &str, password: &str) -
     }
 }
 
-pub async fn fetch_remote(
-    state: &AppState,
-    ap_url: &str,
-) -> Result<(String, String, String, String, String), String> {
+pub struct RemoteUser {
+    pub username: String,
+    pub ap_url: String,
+    pub inbox_url: String,
+    pub shared_inbox_url: Option<String>,
+    pub key_id: Option<String>,
+    pub public_key: Option<String>,
+    pub also_known_as: Vec<String>,
+    pub moved_to: Option<String>,
+    pub is_locked: bool,
+    pub display_name: String,
+    pub bio: String,
+    pub emojis: String,
+    pub avatar_url: Option<String>,
+    pub header_url: Option<String>,
+    // Name and HTML value pairs
+    pub fields: Vec<(String, String)>,
+}
+
+// icon and image are Image objects, links or arrays of them
+fn parse_image_url(image: &Value) -> Option<String> {
+    let image = match image {
+        Value::Array(images) => images.first()?,
+        image => image,
+    };
+    let url = match image {
+        Value::String(url) => url.as_str(),
+        image => match &image["url"] {
+            Value::String(url) => url.as_str(),
+            Value::Array(links) => links.first()?["href"].as_str()?,
+            link => link["href"].as_str()?,
+        },
+    };
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return None;
+    }
+    Some(url.to_string())
+}
+
+pub async fn fetch_remote(state: &AppState, ap_url: &str) -> Result<RemoteUser, String> {
     // Fetch
     let Ok(res) = utils::signed_get(state, ap_url).await else {
         return Err("Failed to fetch remote user".to_string());
@@ -177,6 +291,33 @@ pub async fn fetch_remote(
         return Err("Fetched object does not have an inbox".to_string());
     };
 
+    let shared_inbox_url = user_json["endpoints"]["sharedInbox"]
+        .as_str()
+        .map(|s| s.to_string());
+
+    // Key must live on the actor's host and be owned by the actor
+    let key_id = user_json["publicKey"]["id"]
+        .as_str()
+        .filter(|key_id| utils::url_host(key_id) == utils::url_host(ap_url))
+        .filter(|_| user_json["publicKey"]["owner"].as_str() == Some(ap_url))
+        .map(|s| s.to_string());
+    let public_key = user_json["publicKey"]["publicKeyPem"]
+        .as_str()
+        .map(|s| s.to_string());
+
+    let also_known_as = match &user_json["alsoKnownAs"] {
+        Value::String(alias) => vec![alias.to_string()],
+        Value::Array(aliases) => aliases
+            .iter()
+            .filter_map(|alias| alias.as_str().map(|s| s.to_string()))
+            .collect(),
+        _ => vec![],
+    };
+    let moved_to = user_json["movedTo"].as_str().map(|s| s.to_string());
+    let is_locked = user_json["manuallyApprovesFollowers"]
+        .as_bool()
+        .unwrap_or(false);
+
     let display_name = user_json["name"].as_str().unwrap_or(username);
     let display_name = if display_name.is_empty() {
         username
@@ -184,75 +325,242 @@ pub async fn fetch_remote(
         display_name
     };
 
-    // Merge attachments to bio
-    let mut bio = user_json["summary"]
-        .as_str()
-        .unwrap_or("")
-        .trim_end_matches("</p>")
-        .to_string();
-    if let Some(attachments) = user_json["attachment"].as_array() {
-        bio.push('\n');
-        for attachment in attachments {
-            if let (Some(name), Some(url)) =
-                (attachment["name"].as_str(), attachment["value"].as_str())
-            {
-                bio.push_str(&format!("\n{}: {}", name, url));
-            }
-        }
-    }
-    let bio = utils::strip_content(state, &bio);
+    let bio = user_json["summary"].as_str().unwrap_or("");
+    let bio = utils::strip_content(state, bio);
     let bio = utils::parse_content(state, &bio);
 
+    // Profile fields are PropertyValue attachments
+    let fields = match &user_json["attachment"] {
+        Value::Array(attachments) => attachments.iter().collect(),
+        Value::Object(_) => vec![&user_json["attachment"]],
+        _ => vec![],
+    }
+    .into_iter()
+    .filter(|attachment| attachment["type"] == "PropertyValue")
+    .filter_map(|attachment| {
+        let name = utils::strip_content(state, attachment["name"].as_str()?);
+        let value = utils::strip_content(state, attachment["value"].as_str()?);
+        Some((name, utils::parse_content(state, &value)))
+    })
+    .collect();
+
     let url = Url::parse(ap_url).unwrap();
     let host = url.host_str().unwrap();
 
     let username = format!("{}@{}", username, host);
 
-    Ok((
+    Ok(RemoteUser {
         username,
-        ap_url.to_string(),
-        inbox_url.to_string(),
-        display_name.to_string(),
+        ap_url: ap_url.to_string(),
+        inbox_url: inbox_url.to_string(),
+        shared_inbox_url,
+        key_id,
+        public_key,
+        also_known_as,
+        moved_to,
+        is_locked,
+        display_name: display_name.to_string(),
         bio,
-    ))
+        emojis: utils::parse_emojis(&user_json["tag"]),
+        avatar_url: parse_image_url(&user_json["icon"]),
+        header_url: parse_image_url(&user_json["image"]),
+        fields,
+    })
 }
 
 pub async fn add_remote(state: &AppState, ap_url: &str) -> Result<(), String> {
-    let res = fetch_remote(state, ap_url).await;
-    let Ok((username, ap_url, inbox_url, display_name, bio)) = res else {
-        return Err(format!("Failed to fetch remote user: {}", res.unwrap_err()));
+    let remote = match fetch_remote(state, ap_url).await {
+        Ok(remote) => remote,
+        Err(e) => return Err(format!("Failed to fetch remote user: {}", e)),
     };
 
     queries::user::create(
         state,
-        &username,
-        None,
-        &ap_url,
-        &inbox_url,
+        &remote.username,
         None,
+        &remote.ap_url,
+        &remote.inbox_url,
+        remote.shared_inbox_url.as_deref(),
         None,
-        &display_name,
-        &bio,
+        remote.public_key.as_deref(),
+        remote.key_id.as_deref(),
+        &remote.display_name,
+        &remote.bio,
         &utils::date_now(),
         &utils::date_now(),
         0,
     )
     .await;
 
+    if let Some(user) = queries::user::get_by_ap_url(state, &remote.ap_url).await {
+        apply_remote(state, user.id, &remote).await;
+    }
+
     Ok(())
 }
 
 pub async fn update_remote(state: &AppState, ap_url: &str) -> Result<(), String> {
-    let Ok((_username, ap_url, _inbox_url, display_name, bio)) = fetch_remote(state, ap_url).await
-    else {
+    let Ok(remote) = fetch_remote(state, ap_url).await else {
         return Err("Failed to fetch remote user".to_string());
     };
 
-    let Some(user) = queries::user::get_by_ap_url(state, &ap_url).await else {
+    let Some(user) = queries::user::get_by_ap_url(state, &remote.ap_url).await else {
         return Err("User not found".to_string());
     };
 
-    queries::user::update_profile(state, user.id, &display_name, &bio).await;
+    if let (Some(key_id), Some(public_key)) = (&remote.key_id, &remote.public_key) {
+        queries::user::update_public_key(state, user.id, key_id, public_key).await;
+    }
+    apply_remote(state, user.id, &remote).await;
+
+    Ok(())
+}
+
+// Shared by add_remote and update_remote so both store the same fields
+async fn apply_remote(state: &AppState, user_id: i64, remote: &RemoteUser) {
+    queries::user::update_remote(
+        state,
+        user_id,
+        &remote.display_name,
+        &remote.bio,
+        &remote.inbox_url,
+        remote.shared_inbox_url.as_deref(),
+        &remote.also_known_as.join("\n"),
+        remote.moved_to.as_deref(),
+        remote.is_locked as i64,
+        &remote.emojis,
+        remote.avatar_url.as_deref(),
+        remote.header_url.as_deref(),
+    )
+    .await;
+    replace_fields(state, user_id, &remote.fields).await;
+}
+
+// Resolve @user@host, local username or actor URL, refreshing remote users
+pub async fn resolve(state: &AppState, account: &str) -> Result<UserRecord, String> {
+    let account = account.trim();
+    let ap_url = if account.starts_with("https://") {
+        account.to_string()
+    } else {
+        let acct = account.trim_start_matches('@');
+        let acct = acct
+            .strip_suffix(&format!("@{}", state.domain))
+            .unwrap_or(acct);
+        if let Some(user) = queries::user::get_by_username(state, acct).await
+            && user.is_local == 1
+        {
+            return Ok(user);
+        }
+        let Some(ap_url) = search::resolve_acct(state, acct).await else {
+            return Err(format!("Account not found: {}", account));
+        };
+        ap_url
+    };
+
+    match queries::user::get_by_ap_url(state, &ap_url).await {
+        Some(user) if user.is_local == 1 => return Ok(user),
+        Some(_) => update_remote(state, &ap_url).await?,
+        None => add_remote(state, &ap_url).await?,
+    }
+    match queries::user::get_by_ap_url(state, &ap_url).await {
+        Some(user) => Ok(user),
+        None => Err(format!("Account not found: {}", account)),
+    }
+}
+
+pub async fn update_aliases(state: &AppState, user_id: i64, aliases: &str) -> Result<(), String> {
+    let user = queries::user::get_by_id(state, user_id).await;
+
+    let mut also_known_as: Vec<String> = vec![];
+    for alias in aliases.lines().filter(|line| !line.trim().is_empty()) {
+        let alias = resolve(state, alias).await?;
+        if alias.id == user.id {
+            return Err("Cannot add yourself as an alias".to_string());
+        }
+        if !also_known_as.contains(&alias.ap_url) {
+            also_known_as.push(alias.ap_url);
+        }
+    }
 
+    queries::user::update_aliases(state, user.id, &also_known_as.join("\n")).await;
     Ok(())
 }
+
+pub async fn move_to(state: &AppState, user_id: i64, target: &str) -> Result<(), String> {
+    let user = queries::user::get_by_id(state, user_id).await;
+
+    // Target must list this account as an alias
+    let target = resolve(state, target).await?;
+    if target.id == user.id {
+        return Err("Cannot move to yourself".to_string());
+    }
+    if !target
+        .also_known_as
+        .lines()
+        .any(|alias| alias == user.ap_url)
+    {
+        return Err(format!(
+            "{} must have this account as an alias first",
+            target.username
+        ));
+    }
+
+    queries::user::update_moved_to(state, user.id, Some(&target.ap_url)).await;
+
+    // Remote followers are re-pointed by their servers on Move
+    follow::move_followers(state, user.id, target.id).await;
+
+    Ok(())
+}
+
+pub async fn deliver_move(state: &AppState, user_id: i64) {
+    let user = queries::user::get_by_id(state, user_id).await;
+    let Some(moved_to) = user.moved_to else {
+        return;
+    };
+
+    let move_id = format!("{}#move-{}", user.ap_url, utils::gen_unique_id());
+    let move_activity = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": move_id,
+        "type": "Move",
+        "actor": user.ap_url,
+        "object": user.ap_url,
+        "target": moved_to,
+    });
+    let json_body = move_activity.to_string();
+
+    utils::deliver_to_followers(state, user_id, vec![], &json_body).await;
+}
+
+pub async fn delete(state: &AppState, user_id: i64) {
+    // Fix counters that cascading deletes would leave behind
+    queries::follow::decrement_counts_by_user(state, user_id).await;
+    queries::like::decrement_counts_by_user(state, user_id).await;
+    queries::note::decrement_boost_counts_by_author(state, user_id).await;
+
+    // Uploaded files are not removed by cascade
+    media::delete_user_files(state, user_id).await;
+
+    // Follows, notes, likes, notifications, media and sessions are deleted by cascade
+    queries::user::delete(state, user_id).await;
+}
+
+pub async fn deliver_delete(state: &AppState, user_id: i64) {
+    let user = queries::user::get_by_id(state, user_id).await;
+
+    let delete_activity = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": format!("{}#delete", user.ap_url),
+        "type": "Delete",
+        "actor": user.ap_url,
+        "object": user.ap_url,
+        "to": ["https://www.w3.org/ns/activitystreams#Public"],
+    });
+    let json_body = delete_activity.to_string();
+
+    // Sent right away, queued deliveries are dropped with the user
+    let inboxes = utils::all_known_inboxes(state).await;
+    let private_key = user.private_key.unwrap();
+    delivery::send_now(state, &user.ap_url, &private_key, inboxes, &json_body).await;
+}
@@ -26,6 +26,19 @@ pub fn date_plus_days(days: i64) -> String {
     date.format(&Rfc3339).unwrap()
 }
 
+pub fn date_plus_seconds(seconds: i64) -> String {
+    let date = OffsetDateTime::now_utc() + Duration::seconds(seconds);
+    date.format(&Rfc3339).unwrap()
+}
+
+// Unparsable dates count as past
+pub fn is_past(date: &str) -> bool {
+    let Ok(parsed) = OffsetDateTime::parse(date, &Rfc3339) else {
+        return true;
+    };
+    parsed <= OffsetDateTime::now_utc()
+}
+
 pub const HTTP_DATE_FORMAT: &str =
     "[weekday repr:short], [day] [month repr:short] [year] [hour repr:24]:[minute]:[second] GMT";
 
@@ -136,49 +149,134 @@ pub fn parse_content(state: &AppState, content: &str) -> String {
         &content,
         r#"<a class="link-in-note" href="$1" target="_blank" rel="nofollow noopener">$1</a>"#,
     );
+    let content = state.re.hashtag.replace_all(
+        &content,
+        format!(
+            r#"$1<a class="hashtag-in-note" href="https://{}/tags/$2" rel="tag">#$2</a>"#,
+            state.domain
+        ),
+    );
 
     content.to_string()
 }
 
+// Emoji tags as "shortcode url" lines
+pub fn parse_emojis(tags: &serde_json::Value) -> String {
+    let tags = match tags {
+        serde_json::Value::Array(tags) => tags.iter().collect(),
+        serde_json::Value::Object(_) => vec![tags],
+        _ => vec![],
+    };
+
+    let mut emojis = String::new();
+    for tag in tags {
+        if tag["type"] != "Emoji" {
+            continue;
+        }
+        let (Some(name), Some(url)) = (tag["name"].as_str(), tag["icon"]["url"].as_str()) else {
+            continue;
+        };
+        let shortcode = name.trim_matches(':');
+        if shortcode.is_empty()
+            || !shortcode
+                .bytes()
+                .all(|a| a.is_ascii_alphanumeric() || a == b'_' || a == b'-' || a == b'+')
+            || !url.starts_with("https://")
+            || url.contains(char::is_whitespace)
+        {
+            continue;
+        }
+        emojis.push_str(&format!("{} {}\n", shortcode, url));
+    }
+    emojis
+}
+
+// Replace :shortcode: with inline images
+pub fn emojify(content: &str, emojis: &str) -> String {
+    let mut content = content.to_string();
+    for line in emojis.lines() {
+        let Some((shortcode, url)) = line.split_once(' ') else {
+            continue;
+        };
+        content = content.replace(
+            &format!(":{}:", shortcode),
+            &format!(
+                r#"<img class="emoji" src="{}" alt=":{}:" title=":{}:" height="20">"#,
+                tera::escape_html(url),
+                shortcode,
+                shortcode
+            ),
+        );
+    }
+    content
+}
+
+#[cfg(feature = "api")]
+pub fn emojis_to_value(emojis: &str) -> Vec<serde_json::Value> {
+    emojis
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(shortcode, url)| {
+            serde_json::json!({
+                "shortcode": shortcode,
+                "url": url,
+                "static_url": url,
+                "visible_in_picker": false,
+            })
+        })
+        .collect()
+}
+
+// Lowercased hashtags without '#'
+pub fn extract_hashtags(state: &AppState, content: &str) -> Vec<String> {
+    let content = strip_content(state, content);
+    let mut hashtags: Vec<String> = vec![];
+    for captures in state.re.hashtag.captures_iter(&content) {
+        let hashtag = captures[2].to_lowercase();
+        if !hashtags.contains(&hashtag) {
+            hashtags.push(hashtag);
+        }
+    }
+    hashtags
+}
+
 #[cfg(feature = "api")]
 pub fn attachments_to_value(
-    state: &AppState,
-    attachments: &Option<String>,
+    attachments: &[queries::attachment::AttachmentRecord],
 ) -> Vec<serde_json::Value> {
-    if let Some(attachments) = attachments {
-        if attachments.is_empty() {
-            vec![]
-        } else {
-            let mut ret: Vec<serde_json::Value> = vec![];
-            for attachment in attachments.split("<br>") {
-                let parts = attachment.splitn(2, ": ").collect::<Vec<&str>>();
-                let attachment_type: &str;
-                let url: &str;
-                if parts.len() == 2 {
-                    attachment_type = parts[0];
-                    url = parts[1];
-                } else {
-                    attachment_type = "image";
-                    url = parts[0];
-                }
-
-                let url = strip_content(state, url);
-                if url.is_empty() {
-                    continue;
-                }
-                ret.push(serde_json::json!({
-                    "id": url,
-                    "type": attachment_type,
-                    "url": url,
-                    "preview_url": url,
-                    "description": "",
-                }));
+    attachments
+        .iter()
+        .map(|attachment| {
+            let attachment_type =
+                crate::back::media::attachment_type(attachment.media_type.as_deref().unwrap_or(""));
+            let mut meta = serde_json::json!({});
+            if let (Some(width), Some(height)) = (attachment.width, attachment.height) {
+                meta["original"] = serde_json::json!({
+                    "width": width,
+                    "height": height,
+                });
             }
-            ret
-        }
-    } else {
-        vec![]
-    }
+            if let Some((x, y)) = attachment
+                .focal_point
+                .as_deref()
+                .and_then(|focal_point| focal_point.split_once(','))
+            {
+                meta["focus"] = serde_json::json!({
+                    "x": x.parse::<f64>().unwrap_or(0.0),
+                    "y": y.parse::<f64>().unwrap_or(0.0),
+                });
+            }
+            serde_json::json!({
+                "id": attachment.url,
+                "type": attachment_type,
+                "url": attachment.url,
+                "preview_url": attachment.url,
+                "description": attachment.description,
+                "blurhash": attachment.blurhash,
+                "meta": meta,
+            })
+        })
+        .collect()
 }
 
 pub fn user_url(domain: &str, username: &str) -> String {
@@ -197,33 +295,73 @@ pub fn local_user_outbox_url(domain: &str, username: &str) -> String {
     format!("https://{}/users/{}/outbox", domain, username)
 }
 
+pub fn local_user_followers_url(domain: &str, username: &str) -> String {
+    format!("https://{}/users/{}/followers", domain, username)
+}
+
+pub fn local_user_following_url(domain: &str, username: &str) -> String {
+    format!("https://{}/users/{}/following", domain, username)
+}
+
+pub fn local_user_featured_url(domain: &str, username: &str) -> String {
+    format!("https://{}/users/{}/collections/featured", domain, username)
+}
+
 pub fn note_url(domain: &str, author: &str, id: i64) -> String {
     format!("https://{}/@{}/{}", domain, author, id)
 }
 
+pub fn media_url(domain: &str, file_name: &str) -> String {
+    format!("https://{}/media/{}", domain, file_name)
+}
+
+pub fn hashtag_url(domain: &str, hashtag: &str) -> String {
+    format!("https://{}/tags/{}", domain, hashtag)
+}
+
 pub fn local_note_ap_url(domain: &str, id: i64) -> String {
     format!("https://{}/notes/{}", domain, id)
 }
 
-pub async fn signed_deliver(
+pub async fn signed_deliver(state: &AppState, sender_id: i64, recipient_inbox: &str, body: &str) {
+    println!("Queueing delivery to {}", recipient_inbox);
+
+    // Store in delivery queue, then wake up the worker
+    queries::delivery::create(state, sender_id, recipient_inbox, body, &date_now()).await;
+    state.deliver_notify.notify_one();
+}
+
+pub async fn signed_post(
     state: &AppState,
     sender_ap_url: &str,
     private_key: &str,
     recipient_inbox: &str,
     body: &str,
-) {
-    println!("Delivering to {}: {}", recipient_inbox, body);
-
+) -> Result<reqwest::Response, String> {
     // Sign in blocking task
     let _permit = state.sign_queue.acquire().await.unwrap();
     let (date, digest_value, signed_header) = {
         let sender_ap_url = sender_ap_url.to_string();
         let private_key = private_key.to_string();
-        let recipient_inbox = recipient_inbox.to_string();
         let body = body.to_string();
 
         let date = date_now_http_format();
 
+        let Ok(url_parsed) = Url::parse(recipient_inbox) else {
+            return Err("Invalid URL".to_string());
+        };
+        let Some(host) = url_parsed.host_str() else {
+            return Err("Host missing".to_string());
+        };
+        let host = host.to_string();
+        let path_and_query = {
+            let full = url_parsed.path();
+            match url_parsed.query() {
+                Some(q) => format!("{}?{}", &full, q),
+                None => full.to_string(),
+            }
+        };
+
         task::spawn_blocking(move || {
             let digest_value = {
                 let mut hasher = Sha256::new();
@@ -232,16 +370,6 @@ pub async fn signed_deliver(
                 format!("SHA-256={}", general_purpose::STANDARD.encode(hash))
             };
 
-            let url_parsed = Url::parse(&recipient_inbox).unwrap();
-            let host = url_parsed.host_str().unwrap();
-            let path_and_query = {
-                let full = url_parsed.path();
-                match url_parsed.query() {
-                    Some(q) => format!("{}?{}", &full, q),
-                    None => full.to_string(),
-                }
-            };
-
             let signing_string = format!(
                 "(request-target): post {}\nhost: {}\ndate: {}\ndigest: {}",
                 path_and_query, host, date, digest_value
@@ -266,32 +394,22 @@ pub async fn signed_deliver(
     };
     drop(_permit);
 
-    // Deliver in background queue
-    task::spawn({
-        let deliver_queue = state.deliver_queue.clone();
-        let http_client = state.http_client.clone();
-        let inbox = recipient_inbox.to_string().clone();
-        let body = body.to_string().clone();
-        async move {
-            let _permit = deliver_queue.acquire().await.unwrap();
-
-            let _res = http_client
-                .post(inbox)
-                .header("Date", date)
-                .header("Digest", digest_value)
-                .header("Signature", signed_header)
-                .header("Content-Type", "application/activity+json")
-                .body(body.to_string())
-                .send()
-                .await;
-
-            drop(_permit);
-
-            // let status = _res.as_ref().unwrap().status();
-            // let body = _res.unwrap().text().await.unwrap();
-            // println!("{}, response: {}", status, body);
-        }
-    });
+    // Post
+    let res = state
+        .http_client
+        .post(recipient_inbox)
+        .header("Date", date)
+        .header("Digest", digest_value)
+        .header("Signature", signed_header)
+        .header("Content-Type", "application/activity+json")
+        .body(body.to_string())
+        .send()
+        .await;
+
+    match res {
+        Ok(response) => Ok(response),
+        Err(e) => Err(format!("HTTP POST error: {}", e)),
+    }
 }
 
 pub async fn deliver_to_followers(
@@ -300,36 +418,72 @@ pub async fn deliver_to_followers(
     mention_inboxes: Vec<String>,
     body: &str,
 ) {
-    let sender = queries::user::get_by_id(state, sender_id).await;
-    let private_key = sender.private_key.unwrap();
-
     let followers = queries::follow::get_follower_inboxes(state, sender_id).await;
-    let mut already_delivered_hosts = vec![state.domain.clone()];
-    for follower in followers {
-        let url_parsed = Url::parse(&follower.inbox_url).unwrap();
-        let host = url_parsed.host_str().unwrap().to_string();
-        if !already_delivered_hosts.contains(&host) {
-            signed_deliver(
-                state,
-                &sender.ap_url,
-                &private_key,
-                &follower.inbox_url,
-                body,
-            )
-            .await;
-            already_delivered_hosts.push(host);
+    let followers = followers
+        .into_iter()
+        .map(|follower| (follower.inbox_url, follower.shared_inbox_url));
+    let inboxes = pick_inboxes(&state.domain, followers, mention_inboxes);
+
+    for inbox in inboxes {
+        signed_deliver(state, sender_id, &inbox, body).await;
+    }
+}
+
+pub async fn deliver_to_mentions(
+    state: &AppState,
+    sender_id: i64,
+    mention_inboxes: Vec<String>,
+    body: &str,
+) {
+    let inboxes = pick_inboxes(&state.domain, std::iter::empty(), mention_inboxes);
+
+    for inbox in inboxes {
+        signed_deliver(state, sender_id, &inbox, body).await;
+    }
+}
+
+pub async fn all_known_inboxes(state: &AppState) -> Vec<String> {
+    let remote_users = queries::user::get_remote_inboxes(state).await;
+    let remote_users = remote_users
+        .into_iter()
+        .map(|user| (user.inbox_url, user.shared_inbox_url));
+    pick_inboxes(&state.domain, remote_users, vec![])
+}
+
+fn pick_inboxes(
+    domain: &str,
+    recipients: impl Iterator<Item = (String, Option<String>)>,
+    extra_inboxes: Vec<String>,
+) -> Vec<String> {
+    // One shared inbox per host, or personal inboxes when the host has none
+    let recipients = recipients.collect::<Vec<(String, Option<String>)>>();
+    let mut shared_inbox_hosts = vec![domain.to_string()];
+    let mut inboxes: Vec<String> = vec![];
+    for shared_inbox_url in recipients.iter().filter_map(|(_, shared)| shared.as_ref()) {
+        let host = url_host(shared_inbox_url);
+        if !shared_inbox_hosts.contains(&host) {
+            inboxes.push(shared_inbox_url.clone());
+            shared_inbox_hosts.push(host);
         }
     }
 
-    // Also deliver to mentioned users
-    for mention_inbox in mention_inboxes {
-        let url_parsed = Url::parse(&mention_inbox).unwrap();
-        let host = url_parsed.host_str().unwrap().to_string();
-        if !already_delivered_hosts.contains(&host) {
-            signed_deliver(state, &sender.ap_url, &private_key, &mention_inbox, body).await;
-            already_delivered_hosts.push(host);
+    // Personal and extra inboxes (e.g. mentioned users) not covered by a shared inbox
+    let personal_inboxes = recipients.into_iter().map(|(inbox_url, _)| inbox_url);
+    for inbox_url in personal_inboxes.chain(extra_inboxes) {
+        let host = url_host(&inbox_url);
+        if !shared_inbox_hosts.contains(&host) && !inboxes.contains(&inbox_url) {
+            inboxes.push(inbox_url);
         }
     }
+
+    inboxes
+}
+
+pub fn url_host(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+        .unwrap_or_default()
 }
 
 pub async fn signed_get(state: &AppState, url: &str) -> Result<reqwest::Response, String> {
@@ -394,3 +548,98 @@ pub async fn signed_get(state: &AppState, url: &str) -> Result<reqwest::Response
         Err(e) => Err(format!("HTTP GET error: {}", e)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipient(inbox_url: &str, shared_inbox_url: Option<&str>) -> (String, Option<String>) {
+        (
+            inbox_url.to_string(),
+            shared_inbox_url.map(|url| url.to_string()),
+        )
+    }
+
+    #[test]
+    fn pick_inboxes_prefers_shared_inbox_per_host() {
+        let recipients = vec![
+            recipient("https://a.example/users/1/inbox", None),
+            recipient(
+                "https://a.example/users/2/inbox",
+                Some("https://a.example/inbox"),
+            ),
+            recipient("https://b.example/users/3/inbox", None),
+            recipient("https://b.example/users/4/inbox", None),
+        ];
+        let inboxes = pick_inboxes("local.example", recipients.into_iter(), vec![]);
+        assert_eq!(
+            inboxes,
+            vec![
+                "https://a.example/inbox",
+                "https://b.example/users/3/inbox",
+                "https://b.example/users/4/inbox",
+            ]
+        );
+    }
+
+    #[test]
+    fn pick_inboxes_skips_local_and_duplicate_inboxes() {
+        let recipients = vec![
+            recipient("https://local.example/users/bob/inbox", None),
+            recipient("https://b.example/users/3/inbox", None),
+        ];
+        let extra_inboxes = vec![
+            "https://b.example/users/3/inbox".to_string(),
+            "https://c.example/users/5/inbox".to_string(),
+        ];
+        let inboxes = pick_inboxes("local.example", recipients.into_iter(), extra_inboxes);
+        assert_eq!(
+            inboxes,
+            vec![
+                "https://b.example/users/3/inbox",
+                "https://c.example/users/5/inbox",
+            ]
+        );
+    }
+
+    #[test]
+    fn pick_inboxes_covers_extra_inboxes_by_shared_inbox() {
+        let recipients = vec![recipient(
+            "https://a.example/users/1/inbox",
+            Some("https://a.example/inbox"),
+        )];
+        let extra_inboxes = vec!["https://a.example/users/9/inbox".to_string()];
+        let inboxes = pick_inboxes("local.example", recipients.into_iter(), extra_inboxes);
+        assert_eq!(inboxes, vec!["https://a.example/inbox"]);
+    }
+
+    #[test]
+    fn parse_emojis_keeps_valid_emoji_tags() {
+        let tags = serde_json::json!([
+            {"type": "Emoji", "name": ":blob_cat:", "icon": {"url": "https://r.example/blob_cat.png"}},
+            {"type": "Hashtag", "name": "#cats", "href": "https://r.example/tags/cats"},
+            {"type": "Emoji", "name": ":bad name:", "icon": {"url": "https://r.example/bad.png"}},
+            {"type": "Emoji", "name": ":plain:", "icon": {"url": "http://r.example/plain.png"}},
+            {"type": "Emoji", "name": ":noicon:"},
+        ]);
+        assert_eq!(
+            parse_emojis(&tags),
+            "blob_cat https://r.example/blob_cat.png\n"
+        );
+
+        let tag = serde_json::json!(
+            {"type": "Emoji", "name": "wave", "icon": {"url": "https://r.example/wave.png"}}
+        );
+        assert_eq!(parse_emojis(&tag), "wave https://r.example/wave.png\n");
+    }
+
+    #[test]
+    fn emojify_replaces_known_shortcodes() {
+        let emojis = "blob_cat https://r.example/blob_cat.png?a=1&b=2\n";
+        assert_eq!(
+            emojify("hi :blob_cat: :other:", emojis),
+            r#"hi <img class="emoji" src="https:&#x2F;&#x2F;r.example&#x2F;blob_cat.png?a=1&amp;b=2" alt=":blob_cat:" title=":blob_cat:" height="20"> :other:"#
+        );
+        assert_eq!(emojify("hi :blob_cat:", ""), "hi :blob_cat:");
+    }
+}
@@ -7,9 +7,11 @@ Options:
 
     serve                   Start server
     useradd      <U> <P>    Add user
+    userdel      <U>        Delete user
     passwd       <U> <P>    Change user password
     block        <D>        Block domain
     unblock      <D>        Unblock domain
-    blocklist               List blocked domains"#;
+    blocklist               List blocked domains
+    migrate                 Move data from older database layouts"#;
     println!("{}", help)
 }
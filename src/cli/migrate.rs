@@ -0,0 +1,62 @@
+use crate::back::attachment;
+use crate::back::init;
+use crate::back::queries;
+use crate::back::queries::attachment::AttachmentRecord;
+
+pub async fn migrate() {
+    let state = init::create_app_state().await;
+    let media_prefix = format!("https://{}/media/", state.domain);
+
+    // Non-public notes from before visibility were unlisted
+    if let Some(count) = queries::note::migrate_is_public(&state).await {
+        println!("Migrated visibility of {} unlisted notes.", count);
+    }
+
+    // Move legacy attachment lists into the attachments table
+    let notes = queries::note::get_legacy_attachments(&state).await;
+    for note in &notes {
+        if queries::attachment::get_by_note(&state, note.id)
+            .await
+            .is_empty()
+        {
+            let mut attachments = attachment::parse_legacy(&note.attachments);
+            // Local uploads keep their exact type and description
+            for attachment in &mut attachments {
+                if let Some(file_name) = attachment.url.strip_prefix(&media_prefix)
+                    && let Some(record) = queries::media::get_by_file_name(&state, file_name).await
+                {
+                    attachment.media_type = Some(record.media_type);
+                    attachment.description = record.description;
+                }
+            }
+            attachment::add_remote(&state, note.id, &attachments).await;
+        }
+        queries::note::clear_legacy_attachments(&state, note.id).await;
+    }
+    println!("Migrated attachments of {} notes.", notes.len());
+
+    // Store edit history attachments as JSON
+    let edits = queries::note::get_legacy_edit_attachments(&state).await;
+    for edit in &edits {
+        let attachments = attachment::parse_legacy(&edit.attachments)
+            .into_iter()
+            .map(|attachment| AttachmentRecord {
+                note_id: edit.note_id,
+                media_type: attachment.media_type,
+                url: attachment.url,
+                description: attachment.description,
+                blurhash: None,
+                width: None,
+                height: None,
+                focal_point: None,
+            })
+            .collect::<Vec<AttachmentRecord>>();
+        queries::note::update_edit_attachments(
+            &state,
+            edit.id,
+            &serde_json::to_string(&attachments).unwrap(),
+        )
+        .await;
+    }
+    println!("Migrated attachments of {} edits.", edits.len());
+}
@@ -1,7 +1,9 @@
 mod block;
 mod help;
+mod migrate;
 pub mod parse;
 mod passwd;
 mod serve;
 mod useradd;
+mod userdel;
 mod version;
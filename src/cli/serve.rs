@@ -1,9 +1,11 @@
 use crate::VERSION;
 use crate::activitypub as ap;
+use crate::back::delivery;
 use crate::back::init;
 
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{get, post},
 };
 
@@ -14,12 +16,27 @@ async fn activitypub_routes() -> Router<init::AppState> {
         .route("/nodeinfo/2.1", get(ap::nodeinfo::get_nodeinfo))
         .route("/users/{username}", get(ap::actor::get))
         .route("/users/{username}/inbox", post(ap::inbox::post))
+        .route("/users/{username}/outbox", get(ap::outbox::get))
+        .route(
+            "/users/{username}/followers",
+            get(ap::following::get_followers),
+        )
+        .route(
+            "/users/{username}/following",
+            get(ap::following::get_following),
+        )
+        .route(
+            "/users/{username}/collections/featured",
+            get(ap::featured::get),
+        )
         .route("/inbox", post(ap::inbox::post))
         .route("/notes/{id}", get(ap::note::get))
+        .route("/media/{file_name}", get(ap::media::get))
 }
 
 #[cfg(feature = "web")]
-async fn web_routes() -> Router<init::AppState> {
+async fn web_routes(max_media_bytes: usize) -> Router<init::AppState> {
+    use crate::back::media;
     use crate::web;
     use tower_http::services::ServeDir;
     let router = Router::new()
@@ -31,23 +48,59 @@ async fn web_routes() -> Router<init::AppState> {
             "/profile",
             get(web::profile::get).post(web::profile::post_profile),
         )
+        .route("/profile/fields", post(web::profile::post_fields))
+        .route(
+            "/profile/images",
+            post(web::profile::post_images)
+                .layer(DefaultBodyLimit::max(media::body_limit(max_media_bytes, 2))),
+        )
         .route("/change_password", post(web::profile::post_password))
+        .route("/aliases", post(web::profile::post_aliases))
+        .route("/move", post(web::profile::post_move))
+        .route("/delete_account", post(web::profile::post_delete_account))
         .route("/@{username}", get(web::user::get))
         .route("/@{username}/follow", post(web::follow::post_follow))
         .route("/@{username}/unfollow", post(web::follow::post_unfollow))
         .route("/@{username}/following", get(web::following::get_following))
         .route("/@{username}/followers", get(web::following::get_followers))
-        .route("/new", get(web::new::get).post(web::new::post))
+        .route(
+            "/new",
+            get(web::new::get).merge(post(web::new::post).layer(DefaultBodyLimit::max(
+                media::body_limit(max_media_bytes, media::MAX_ATTACHMENTS),
+            ))),
+        )
         .route("/@{username}/{id}", get(web::note::get))
         .route("/@{username}/{id}/delete", post(web::delete::post))
+        .route(
+            "/@{username}/{id}/edit",
+            get(web::edit::get).post(web::edit::post),
+        )
         .route("/@{username}/{id}/like", post(web::like::post_like))
         .route("/@{username}/{id}/unlike", post(web::like::post_unlike))
+        .route("/@{username}/{id}/vote", post(web::poll::post_vote))
+        .route("/@{username}/{id}/pin", post(web::pin::post_pin))
+        .route("/@{username}/{id}/unpin", post(web::pin::post_unpin))
         .route("/@{username}/{id}/boost", post(web::boost::post_boost))
         .route("/@{username}/{id}/unboost", post(web::boost::post_unboost))
         .route("/notifications", get(web::notifications::get))
+        .route("/conversations", get(web::conversations::get))
+        .route(
+            "/conversations/{id}",
+            get(web::conversations::get_conversation),
+        )
+        .route("/follow_requests", get(web::follow_requests::get))
+        .route(
+            "/follow_requests/{username}/authorize",
+            post(web::follow_requests::post_authorize),
+        )
+        .route(
+            "/follow_requests/{username}/reject",
+            post(web::follow_requests::post_reject),
+        )
         .route("/home", get(web::timeline::get_home))
         .route("/local", get(web::timeline::get_local))
         .route("/federated", get(web::timeline::get_federated))
+        .route("/tags/{hashtag}", get(web::timeline::get_tag))
         .route("/search", get(web::search::get).post(web::search::post))
         .nest_service(
             "/static",
@@ -61,9 +114,10 @@ async fn web_routes() -> Router<init::AppState> {
 }
 
 #[cfg(feature = "api")]
-async fn api_routes() -> Router<init::AppState> {
+async fn api_routes(max_media_bytes: usize) -> Router<init::AppState> {
     use crate::api;
-    use axum::routing::{delete, patch};
+    use crate::back::media;
+    use axum::routing::{delete, patch, put};
     use tower_http::cors::{Any, CorsLayer};
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -95,7 +149,8 @@ async fn api_routes() -> Router<init::AppState> {
         )
         .route(
             "/api/v1/accounts/update_credentials",
-            patch(api::update_credentials::patch),
+            patch(api::update_credentials::patch)
+                .layer(DefaultBodyLimit::max(media::body_limit(max_media_bytes, 2))),
         )
         .route(
             "/api/v1/accounts/relationships",
@@ -117,9 +172,28 @@ async fn api_routes() -> Router<init::AppState> {
             "/api/v1/accounts/{username}/followers",
             get(api::following::get_followers),
         )
+        .route(
+            "/api/v1/media",
+            post(api::media::post)
+                .layer(DefaultBodyLimit::max(media::body_limit(max_media_bytes, 1))),
+        )
+        .route(
+            "/api/v2/media",
+            post(api::media::post)
+                .layer(DefaultBodyLimit::max(media::body_limit(max_media_bytes, 1))),
+        )
         .route("/api/v1/statuses", post(api::statuses::post))
         .route("/api/v1/statuses/{id}", get(api::statuses::get))
         .route("/api/v1/statuses/{id}", delete(api::statuses::delete))
+        .route("/api/v1/statuses/{id}", put(api::statuses::put))
+        .route(
+            "/api/v1/statuses/{id}/history",
+            get(api::statuses::get_history),
+        )
+        .route(
+            "/api/v1/statuses/{id}/source",
+            get(api::statuses::get_source),
+        )
         .route("/api/v1/statuses/{id}/context", get(api::context::get))
         .route(
             "/api/v1/statuses/{id}/favourite",
@@ -129,6 +203,8 @@ async fn api_routes() -> Router<init::AppState> {
             "/api/v1/statuses/{id}/unfavourite",
             post(api::favourite::post_unfavourite),
         )
+        .route("/api/v1/statuses/{id}/pin", post(api::pin::post_pin))
+        .route("/api/v1/statuses/{id}/unpin", post(api::pin::post_unpin))
         .route(
             "/api/v1/statuses/{id}/reblog",
             post(api::reblog::post_reblog),
@@ -137,9 +213,33 @@ async fn api_routes() -> Router<init::AppState> {
             "/api/v1/statuses/{id}/unreblog",
             post(api::reblog::post_unreblog),
         )
+        .route("/api/v1/polls/{id}", get(api::polls::get))
+        .route("/api/v1/polls/{id}/votes", post(api::polls::post_votes))
         .route("/api/v1/timelines/home", get(api::timeline::get_home))
         .route("/api/v1/timelines/public", get(api::timeline::get_public))
+        .route(
+            "/api/v1/timelines/tag/{hashtag}",
+            get(api::timeline::get_tag),
+        )
         .route("/api/v1/notifications", get(api::notifications::get))
+        .route("/api/v1/conversations", get(api::conversations::get))
+        .route(
+            "/api/v1/conversations/{id}",
+            delete(api::conversations::delete),
+        )
+        .route(
+            "/api/v1/conversations/{id}/read",
+            post(api::conversations::post_read),
+        )
+        .route("/api/v1/follow_requests", get(api::follow_requests::get))
+        .route(
+            "/api/v1/follow_requests/{username}/authorize",
+            post(api::follow_requests::post_authorize),
+        )
+        .route(
+            "/api/v1/follow_requests/{username}/reject",
+            post(api::follow_requests::post_reject),
+        )
         .route("/api/v1/search", get(api::search::get))
         .route("/api/v2/search", get(api::search::get))
         .route("/api/v1/lists", get(api::lists::get))
@@ -154,11 +254,14 @@ pub async fn serve() {
 
     let state = init::create_app_state().await;
 
+    // Start delivery queue worker
+    tokio::spawn(delivery::worker(state.clone()));
+
     let app = activitypub_routes().await;
     #[cfg(feature = "web")]
-    let app = app.merge(web_routes().await);
+    let app = app.merge(web_routes(state.config.max_media_bytes).await);
     #[cfg(feature = "api")]
-    let app = app.merge(api_routes().await);
+    let app = app.merge(api_routes(state.config.max_media_bytes).await);
 
     let app = app.with_state(state);
 
@@ -182,11 +285,14 @@ pub async fn serve() {
 
     let state = init::create_app_state().await;
 
+    // Start delivery queue worker
+    tokio::spawn(delivery::worker(state.clone()));
+
     let app = activitypub_routes().await;
     #[cfg(feature = "web")]
-    let app = app.merge(web_routes().await);
+    let app = app.merge(web_routes(state.config.max_media_bytes).await);
     #[cfg(feature = "api")]
-    let app = app.merge(api_routes().await);
+    let app = app.merge(api_routes(state.config.max_media_bytes).await);
 
     let app = app.with_state(state);
 
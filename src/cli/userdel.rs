@@ -0,0 +1,27 @@
+use crate::back::init;
+use crate::back::queries;
+use crate::back::user;
+
+pub async fn userdel(args: Vec<String>) {
+    if args.len() != 1 {
+        println!("Usage: userdel <username>");
+        return;
+    }
+
+    let username = &args[0];
+
+    let state = init::create_app_state().await;
+    let Some(user) = queries::user::get_by_username(&state, username).await else {
+        println!("User not found.");
+        return;
+    };
+    if user.is_local == 0 {
+        println!("User is not local.");
+        return;
+    }
+
+    // Delete is sent right away, queued deliveries are dropped with the user
+    user::deliver_delete(&state, user.id).await;
+    user::delete(&state, user.id).await;
+    println!("User deleted successfully.");
+}
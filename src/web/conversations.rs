@@ -0,0 +1,129 @@
+use crate::back::attachment;
+use crate::back::conversation;
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::back::queries::note::NoteWithAuthorRecord;
+use crate::back::quote;
+use crate::back::utils::extract_until_id;
+use crate::web::auth::AuthUser;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::{Html, IntoResponse},
+};
+
+#[derive(serde::Deserialize)]
+pub struct PageQuery {
+    pub until: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+struct ConversationItem {
+    id: i64,
+    is_unread: bool,
+    participants: Vec<String>,
+    last_note: NoteWithAuthorRecord,
+}
+
+pub async fn get(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<PageQuery>,
+) -> impl IntoResponse {
+    // Get conversations
+    let (until_date, until_id) = extract_until_id(&state, query.until).await;
+    let conversations = queries::conversation::get_by_user(
+        &state,
+        user.id,
+        &until_date,
+        until_id,
+        state.web_config.max_timeline_items,
+    )
+    .await;
+    let until_next = if let Some(last) = conversations.last() {
+        last.last_note_id
+    } else {
+        until_id
+    };
+
+    let mut items = vec![];
+    for conversation in conversations {
+        let Some(last_note) =
+            queries::note::get_with_author_by_id(&state, conversation.last_note_id).await
+        else {
+            continue;
+        };
+        let participants =
+            conversation::get_participants(&state, &conversation.participants, user.id)
+                .await
+                .into_iter()
+                .map(|participant| participant.username)
+                .collect();
+        items.push(ConversationItem {
+            id: conversation.id,
+            is_unread: conversation.is_unread == 1,
+            participants,
+            last_note,
+        });
+    }
+
+    // Rendering
+    let mut context = tera::Context::new();
+    context.insert("instance_name", &state.metadata.instance_name);
+    context.insert("timezone", &state.web_config.timezone);
+    context.insert("conversations", &items);
+    context.insert("until_next", &until_next);
+    context.insert("max_conversations", &state.web_config.max_timeline_items);
+    let rendered = state.tera.render("conversations.html", &context).unwrap();
+
+    Html(rendered).into_response()
+}
+
+pub async fn get_conversation(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    // Get conversation
+    let Some(conversation) = queries::conversation::get_by_id_and_user(&state, id, user.id).await
+    else {
+        return "Conversation not found".into_response();
+    };
+
+    // Mark as read
+    queries::conversation::update_read(&state, conversation.id, user.id).await;
+
+    // Get notes, oldest first
+    let mut notes = queries::timeline::get_conversation(
+        &state,
+        conversation.id,
+        state.web_config.max_timeline_items,
+    )
+    .await;
+    notes.reverse();
+    attachment::fill(&state, &mut notes).await;
+    quote::fill(&state, &mut notes).await;
+
+    // Mention everyone else in the reply
+    let participants = conversation::get_participants(&state, &conversation.participants, user.id)
+        .await
+        .into_iter()
+        .map(|participant| participant.username)
+        .collect::<Vec<String>>();
+    let reply_content = participants
+        .iter()
+        .map(|username| format!("@{} ", username))
+        .collect::<String>();
+
+    // Rendering
+    let mut context = tera::Context::new();
+    context.insert("instance_name", &state.metadata.instance_name);
+    context.insert("timezone", &state.web_config.timezone);
+    context.insert("participants", &participants);
+    context.insert("notes", &notes);
+    context.insert("last_note_id", &conversation.last_note_id);
+    context.insert("reply_content", &reply_content);
+    let rendered = state.tera.render("conversation.html", &context).unwrap();
+
+    Html(rendered).into_response()
+}
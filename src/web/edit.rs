@@ -0,0 +1,85 @@
+use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::queries;
+use crate::back::utils;
+use crate::web::auth::AuthUser;
+
+use axum::{
+    extract::{Form, Path, State},
+    response::{Html, IntoResponse, Redirect},
+};
+
+pub async fn get(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((username, id)): Path<(String, i64)>,
+) -> impl IntoResponse {
+    // Get note
+    let Some(note) = queries::note::get_by_id(&state, id).await else {
+        return "Note not found".into_response();
+    };
+
+    // Check ownership
+    if note.author_id != user.id || note.boosted_id.is_some() {
+        return "Unauthorized".into_response();
+    }
+
+    // Raw content for editing
+    let content = utils::strip_content(&state, &note.content);
+
+    let mut context = tera::Context::new();
+    context.insert("instance_name", &state.metadata.instance_name);
+    context.insert("username", &username);
+    context.insert("id", &id);
+    context.insert("content", &content);
+    context.insert("summary", &note.summary);
+    context.insert("is_sensitive", &(note.is_sensitive == 1));
+    let rendered = state.tera.render("edit.html", &context).unwrap();
+
+    Html(rendered).into_response()
+}
+
+#[derive(serde::Deserialize)]
+pub struct EditNoteForm {
+    pub content: String,
+    pub summary: Option<String>,
+    pub sensitive: Option<String>,
+}
+
+pub async fn post(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((username, id)): Path<(String, i64)>,
+    Form(form): Form<EditNoteForm>,
+) -> impl IntoResponse {
+    // Get note
+    let Some(existing) = queries::note::get_by_id(&state, id).await else {
+        return "Note not found".into_response();
+    };
+
+    // Check ownership
+    if existing.author_id != user.id {
+        return "Unauthorized".into_response();
+    }
+
+    // Update
+    let res = note::update(
+        &state,
+        id,
+        &form.content,
+        form.summary,
+        form.sensitive.is_some(),
+    )
+    .await;
+    let is_changed = match res {
+        Ok(is_changed) => is_changed,
+        Err(e) => return e.into_response(),
+    };
+
+    // Deliver to followers and mentioned users
+    if is_changed {
+        note::deliver_update(&state, id).await;
+    }
+
+    Redirect::to(&format!("/@{}/{}", username, id)).into_response()
+}
@@ -24,7 +24,10 @@ pub async fn post_follow(
         Ok(_) => {
             // When local user
             if followee.is_local == 1 {
-                follow::accept(&state, auth_user.id, followee.id).await;
+                // Locked users approve follow requests themselves
+                if followee.is_locked == 0 {
+                    follow::accept(&state, auth_user.id, followee.id).await;
+                }
 
             // When remote user
             } else {
@@ -0,0 +1,98 @@
+use crate::back::follow;
+use crate::back::init::AppState;
+use crate::back::queries;
+use crate::web::auth::AuthUser;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::{Html, IntoResponse, Redirect},
+};
+
+#[derive(serde::Deserialize)]
+pub struct FollowRequestsQuery {
+    pub max: Option<String>,
+}
+
+pub async fn get(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<FollowRequestsQuery>,
+) -> impl IntoResponse {
+    // extract max
+    let max_username = query.max.unwrap_or("".to_string());
+
+    // Get follow requests
+    let requests = queries::follow::get_requests(
+        &state,
+        user.id,
+        &max_username,
+        state.web_config.max_timeline_items,
+    )
+    .await;
+
+    let max_next = if let Some(last) = requests.last() {
+        &last.username
+    } else {
+        ""
+    };
+
+    // Render
+    let mut context = tera::Context::new();
+    context.insert("instance_name", &state.metadata.instance_name);
+    context.insert("users", &requests);
+    context.insert("max_next", max_next);
+    context.insert("max_users", &state.web_config.max_timeline_items);
+    let rendered = state.tera.render("follow_requests.html", &context).unwrap();
+
+    Html(rendered).into_response()
+}
+
+pub async fn post_authorize(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(follower_username): Path<String>,
+) -> impl IntoResponse {
+    // Get follower user
+    let Some(follower) = queries::user::get_by_username(&state, &follower_username).await else {
+        return "user not found".into_response();
+    };
+
+    // Approve
+    if let Err(e) = follow::approve(&state, follower.id, user.id).await {
+        return e.into_response();
+    }
+    if follower.is_local == 0 {
+        follow::deliver_accept(&state, follower.id, user.id).await;
+    }
+
+    Redirect::to("/follow_requests").into_response()
+}
+
+pub async fn post_reject(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(follower_username): Path<String>,
+) -> impl IntoResponse {
+    // Get follower user
+    let Some(follower) = queries::user::get_by_username(&state, &follower_username).await else {
+        return "user not found".into_response();
+    };
+
+    // Check if requested
+    match queries::follow::get(&state, follower.id, user.id).await {
+        Some(follow) if follow.pending == 1 => {}
+        _ => return "follow request not found".into_response(),
+    }
+
+    // Deliver reject activity
+    if follower.is_local == 0 {
+        follow::deliver_reject(&state, follower.id, user.id).await;
+    }
+
+    // Reject
+    if let Err(e) = follow::reject(&state, follower.id, user.id).await {
+        return e.into_response();
+    }
+
+    Redirect::to("/follow_requests").into_response()
+}
@@ -1,7 +1,10 @@
 pub mod auth;
 pub mod boost;
+pub mod conversations;
 pub mod delete;
+pub mod edit;
 pub mod follow;
+pub mod follow_requests;
 pub mod following;
 pub mod index;
 pub mod like;
@@ -10,6 +13,8 @@ pub mod logout;
 pub mod new;
 pub mod note;
 pub mod notifications;
+pub mod pin;
+pub mod poll;
 pub mod profile;
 pub mod search;
 pub mod signup;
@@ -1,51 +1,115 @@
+use crate::back::attachment;
 use crate::back::init::AppState;
+use crate::back::media;
 use crate::back::note;
+use crate::back::poll;
 use crate::back::queries;
+use crate::back::quote;
 use crate::back::utils;
 use crate::web::auth::AuthUser;
 
 use axum::{
-    extract::{Form, Query, State},
+    extract::{Multipart, Query, State},
     response::{Html, IntoResponse, Redirect},
 };
 
 #[derive(serde::Deserialize)]
 pub struct NewNoteQuery {
     pub parent_id: Option<i64>,
+    pub quote_id: Option<i64>,
 }
 
 pub async fn get(
     State(state): State<AppState>,
     Query(query): Query<NewNoteQuery>,
-    _user: AuthUser,
-) -> Html<String> {
-    let parent = if let Some(parent_id) = query.parent_id {
+    user: AuthUser,
+) -> impl IntoResponse {
+    let mut parent = if let Some(parent_id) = query.parent_id {
+        let Some(parent_record) = queries::note::get_by_id(&state, parent_id).await else {
+            return "Parent note not found".into_response();
+        };
+        if !note::can_view(&state, &parent_record, Some(user.id)).await {
+            return "Parent note not found".into_response();
+        }
         queries::note::get_with_author_by_id(&state, parent_id).await
     } else {
         None
     };
+    if let Some(parent) = &mut parent {
+        attachment::fill_one(&state, parent).await;
+        quote::fill_one(&state, parent).await;
+    }
+    let quote = if let Some(quote_id) = query.quote_id {
+        quote::get_quotable(&state, quote_id).await
+    } else {
+        None
+    };
 
     let mut context = tera::Context::new();
     context.insert("instance_name", &state.metadata.instance_name);
     context.insert("parent_id", &query.parent_id);
     context.insert("parent", &parent);
+    context.insert("quote", &quote);
     context.insert("timezone", &state.web_config.timezone);
     let rendered = state.tera.render("new.html", &context).unwrap();
 
-    Html(rendered)
+    Html(rendered).into_response()
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Default)]
 pub struct NewNoteForm {
     pub content: String,
     pub parent_id: Option<i64>,
+    pub quote_id: Option<i64>,
+    pub visibility: Option<String>,
+    pub summary: Option<String>,
+    pub poll_options: Option<String>,
+    pub poll_expires_in: Option<i64>,
+    pub poll_multiple: Option<String>,
 }
 
 pub async fn post(
     State(state): State<AppState>,
     user: AuthUser,
-    Form(form): Form<NewNoteForm>,
+    mut multipart: Multipart,
 ) -> impl IntoResponse {
+    // Read fields and files
+    let mut form = NewNoteForm::default();
+    let mut files: Vec<(String, Vec<u8>)> = vec![];
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let Some(name) = field.name().map(|name| name.to_string()) else {
+            continue;
+        };
+        if name == "files" {
+            if files.len() == media::MAX_ATTACHMENTS {
+                return format!("At most {} attachments allowed", media::MAX_ATTACHMENTS)
+                    .into_response();
+            }
+            let media_type = field.content_type().unwrap_or("").to_string();
+            match media::read_field(&state, field).await {
+                Ok(data) if !data.is_empty() => files.push((media_type, data)),
+                Ok(_) => {}
+                Err(e) => return e.into_response(),
+            }
+            continue;
+        }
+        let value = match media::read_text_field(field).await {
+            Ok(value) => value,
+            Err(e) => return e.into_response(),
+        };
+        match name.as_str() {
+            "content" => form.content = value,
+            "parent_id" => form.parent_id = value.parse().ok(),
+            "quote_id" => form.quote_id = value.parse().ok(),
+            "visibility" => form.visibility = Some(value),
+            "summary" => form.summary = Some(value),
+            "poll_options" => form.poll_options = Some(value),
+            "poll_expires_in" => form.poll_expires_in = value.parse().ok(),
+            "poll_multiple" => form.poll_multiple = Some(value),
+            _ => {}
+        }
+    }
+
     let id = utils::gen_unique_id();
     let ap_url = utils::local_note_ap_url(&state.domain, id);
     let created_at = utils::date_now();
@@ -55,12 +119,59 @@ pub async fn post(
         let Some(parent) = queries::note::get_by_id(&state, parent_id).await else {
             return "Parent note not found".into_response();
         };
+        if !note::can_view(&state, &parent, Some(user.id)).await {
+            return "Parent note not found".into_response();
+        }
         let parent_author = queries::user::get_by_id(&state, parent.author_id).await;
         Some(parent_author.username)
     } else {
         None
     };
 
+    if let Some(quote_id) = form.quote_id
+        && let Err(e) = quote::validate(&state, quote_id).await
+    {
+        return e.into_response();
+    }
+
+    // Poll options are one per line
+    let poll_options = form
+        .poll_options
+        .unwrap_or_default()
+        .lines()
+        .map(|option| option.to_string())
+        .filter(|option| !option.trim().is_empty())
+        .collect::<Vec<String>>();
+    let poll_expires_in = form.poll_expires_in.unwrap_or(86400);
+    if !poll_options.is_empty()
+        && let Err(e) = poll::validate(&poll_options, poll_expires_in)
+    {
+        return e.into_response();
+    }
+
+    let visibility = form.visibility.as_deref().unwrap_or("public");
+    if let Err(e) = note::check_length(&state, &form.content)
+        .and_then(|_| note::validate(&state, &form.content, visibility))
+    {
+        return e.into_response();
+    }
+
+    // Save files
+    let mut media_ids = vec![];
+    for (media_type, data) in files {
+        match media::add(&state, user.id, &media_type, &data, None).await {
+            Ok(media_id) => media_ids.push(media_id),
+            Err(e) => {
+                let saved = media::get_unattached(&state, user.id, &media_ids).await;
+                media::delete(&state, &saved.unwrap_or_default()).await;
+                return e.into_response();
+            }
+        }
+    }
+    let media = media::get_unattached(&state, user.id, &media_ids)
+        .await
+        .unwrap();
+
     // Create note
     let res = note::add(
         &state,
@@ -68,18 +179,36 @@ pub async fn post(
         &ap_url,
         user.id,
         &form.content,
-        None,
         form.parent_id,
         parent_author_username,
         &created_at,
-        1, // is_public
+        visibility,
+        form.summary,
+        false,
     )
     .await;
 
     if let Err(e) = res {
         println!("Error creating note: {}", e);
+        media::delete(&state, &media).await;
         return "Something went wrong".into_response();
     }
+    media::attach(&state, id, &media).await;
+    if let Some(quote_id) = form.quote_id {
+        let _ = quote::add(&state, id, quote_id).await;
+    }
+
+    // Create poll
+    if !poll_options.is_empty() {
+        let _ = poll::add(
+            &state,
+            id,
+            &poll_options,
+            poll_expires_in,
+            form.poll_multiple.is_some(),
+        )
+        .await;
+    }
 
     // Deliver to followers and parent
     note::deliver_create(&state, id).await;
@@ -1,5 +1,9 @@
+use crate::back::attachment;
 use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::poll;
 use crate::back::queries;
+use crate::back::quote;
 use crate::web::auth::MaybeAuthUser;
 
 use axum::{
@@ -27,7 +31,7 @@ pub async fn get(
     }
 
     // Get note
-    let Some(note) = queries::note::get_with_author_by_id(&state, id).await else {
+    let Some(mut note) = queries::note::get_with_author_by_id(&state, id).await else {
         return "Note not found".into_response();
     };
 
@@ -37,14 +41,11 @@ pub async fn get(
     };
 
     // Check privacy
-    if note.is_public == 0 {
-        if let Some(auth_user_id) = user.id {
-            let Some(_follow) = queries::follow::get(&state, auth_user_id, author.id).await else {
-                return "Private note".into_response();
-            };
-        } else {
-            return "Private note".into_response();
-        }
+    let Some(note_record) = queries::note::get_by_id(&state, id).await else {
+        return "Note not found".into_response();
+    };
+    if !note::can_view(&state, &note_record, user.id).await {
+        return "Private note".into_response();
     }
 
     // Check is_liked, is_boosted, is_you
@@ -74,24 +75,53 @@ pub async fn get(
         is_you = false;
     }
 
+    // Check pin
+    let is_pinned = is_you
+        && queries::pin::get(&state, author.id, note.id)
+            .await
+            .is_some();
+
     // Get parent
-    let parent = if let Some(parent_id) = note.parent_id {
+    let mut parent = if let Some(parent_id) = note.parent_id
+        && let Some(parent_record) = queries::note::get_by_id(&state, parent_id).await
+        && note::can_view(&state, &parent_record, user.id).await
+    {
         queries::note::get_with_author_by_id(&state, parent_id).await
     } else {
         None
     };
 
     // Get replies
-    let replies = queries::note::get_replies_by_parent_id(&state, id).await;
+    let mut replies =
+        queries::note::get_replies_by_parent_id(&state, id, user.id.unwrap_or(0)).await;
+
+    // Get attachments
+    attachment::fill_one(&state, &mut note).await;
+    quote::fill_one(&state, &mut note).await;
+    if let Some(parent) = &mut parent {
+        attachment::fill_one(&state, parent).await;
+        quote::fill_one(&state, parent).await;
+    }
+    attachment::fill(&state, &mut replies).await;
+    quote::fill(&state, &mut replies).await;
+
+    // Get poll
+    let poll = poll::get_view(&state, id, user.id).await;
+
+    // Get last edit date
+    let edited_at = note_record.updated_at;
 
     let mut context = tera::Context::new();
     context.insert("instance_name", &state.metadata.instance_name);
     context.insert("timezone", &state.web_config.timezone);
     context.insert("parent", &parent);
     context.insert("note", &note);
+    context.insert("edited_at", &edited_at);
+    context.insert("poll", &poll);
     context.insert("is_liked", &is_liked);
     context.insert("is_boosted", &is_boosted);
     context.insert("is_you", &is_you);
+    context.insert("is_pinned", &is_pinned);
     context.insert("replies", &replies);
     let rendered = state.tera.render("note.html", &context).unwrap();
 
@@ -0,0 +1,42 @@
+use crate::back::init::AppState;
+use crate::back::pin;
+use crate::web::auth::AuthUser;
+
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Redirect},
+};
+
+pub async fn post_pin(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((username, id)): Path<(String, i64)>,
+) -> impl IntoResponse {
+    // Pin
+    let res = pin::pin(&state, user.id, id).await;
+    if let Err(e) = res {
+        return e.into_response();
+    };
+
+    // Deliver pin
+    pin::deliver_pin(&state, user.id, id).await;
+
+    Redirect::to(&format!("/@{}/{}", username, id)).into_response()
+}
+
+pub async fn post_unpin(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((username, id)): Path<(String, i64)>,
+) -> impl IntoResponse {
+    // Unpin
+    let res = pin::unpin(&state, user.id, id).await;
+    if let Err(e) = res {
+        return e.into_response();
+    };
+
+    // Deliver unpin
+    pin::deliver_unpin(&state, user.id, id).await;
+
+    Redirect::to(&format!("/@{}/{}", username, id)).into_response()
+}
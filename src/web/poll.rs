@@ -0,0 +1,47 @@
+use crate::back::init::AppState;
+use crate::back::note;
+use crate::back::poll;
+use crate::back::queries;
+use crate::web::auth::AuthUser;
+
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Redirect},
+};
+
+// Checkboxes repeat the choice field, so the body is parsed by hand
+pub async fn post_vote(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((username, id)): Path<(String, i64)>,
+    body: String,
+) -> impl IntoResponse {
+    let choices = url::form_urlencoded::parse(body.as_bytes())
+        .filter(|(key, _)| key == "choice")
+        .filter_map(|(_, value)| value.parse::<i64>().ok())
+        .collect::<Vec<i64>>();
+
+    // Get note
+    let Some(note) = queries::note::get_by_id(&state, id).await else {
+        return "Note not found".into_response();
+    };
+    if !note::can_view(&state, &note, Some(user.id)).await {
+        return "Note not found".into_response();
+    }
+
+    // Get author
+    let author = queries::user::get_by_id(&state, note.author_id).await;
+
+    // Vote
+    let res = poll::vote(&state, user.id, id, &choices).await;
+    if let Err(e) = res {
+        return e.into_response();
+    };
+
+    // Deliver vote
+    if author.is_local == 0 {
+        poll::deliver_vote(&state, user.id, id, &choices).await;
+    }
+
+    Redirect::to(&format!("/@{}/{}", username, id)).into_response()
+}
@@ -1,11 +1,13 @@
 use crate::back::init::AppState;
+use crate::back::media;
 use crate::back::queries;
+use crate::back::queries::profile_field::ProfileFieldRecord;
 use crate::back::user;
 use crate::back::utils;
 use crate::web::auth::AuthUser;
 
 use axum::{
-    extract::{Form, State},
+    extract::{Form, Multipart, State},
     response::{Html, IntoResponse, Redirect},
 };
 
@@ -13,6 +15,22 @@ pub async fn get(State(state): State<AppState>, user: AuthUser) -> impl IntoResp
     let user = queries::user::get_by_id(&state, user.id).await;
     let bio = utils::strip_content(&state, &user.bio);
 
+    // Raw values, padded with empty fields to fill the form
+    let mut fields = queries::profile_field::get_by_user(&state, user.id)
+        .await
+        .into_iter()
+        .map(|field| ProfileFieldRecord {
+            value: utils::strip_content(&state, &field.value),
+            name: field.name,
+        })
+        .collect::<Vec<ProfileFieldRecord>>();
+    while fields.len() < user::MAX_FIELDS {
+        fields.push(ProfileFieldRecord {
+            name: "".to_string(),
+            value: "".to_string(),
+        });
+    }
+
     #[cfg(feature = "api")]
     let oauth_tokens = queries::oauth::get_tokens(&state, user.id).await;
 
@@ -20,6 +38,12 @@ pub async fn get(State(state): State<AppState>, user: AuthUser) -> impl IntoResp
     context.insert("instance_name", &state.metadata.instance_name);
     context.insert("display_name", &user.display_name);
     context.insert("bio", &bio);
+    context.insert("is_locked", &(user.is_locked == 1));
+    context.insert("fields", &fields);
+    context.insert("avatar_url", &user.avatar_url);
+    context.insert("header_url", &user.header_url);
+    context.insert("also_known_as", &user.also_known_as);
+    context.insert("moved_to", &user.moved_to);
     #[cfg(feature = "api")]
     context.insert("oauth_tokens", &oauth_tokens);
     let rendered = state.tera.render("profile.html", &context).unwrap();
@@ -31,6 +55,7 @@ pub async fn get(State(state): State<AppState>, user: AuthUser) -> impl IntoResp
 pub struct ProfileForm {
     pub display_name: String,
     pub bio: String,
+    pub is_locked: Option<String>,
 }
 
 pub async fn post_profile(
@@ -39,10 +64,109 @@ pub async fn post_profile(
     Form(form): Form<ProfileForm>,
 ) -> impl IntoResponse {
     user::update_profile(&state, user.id, &form.display_name, &form.bio).await;
+    queries::user::update_locked(&state, user.id, form.is_locked.is_some() as i64).await;
 
     Redirect::to("/home").into_response()
 }
 
+// Each field repeats the name and value inputs, so the body is parsed by hand
+pub async fn post_fields(
+    State(state): State<AppState>,
+    user: AuthUser,
+    body: String,
+) -> impl IntoResponse {
+    let pairs = url::form_urlencoded::parse(body.as_bytes()).collect::<Vec<_>>();
+    let names = pairs.iter().filter(|(key, _)| key == "name");
+    let values = pairs.iter().filter(|(key, _)| key == "value");
+    let fields = names
+        .zip(values)
+        .map(|((_, name), (_, value))| (name.to_string(), value.to_string()))
+        .collect::<Vec<(String, String)>>();
+
+    if let Err(e) = user::update_fields(&state, user.id, &fields).await {
+        return e.into_response();
+    }
+
+    Redirect::to("/profile").into_response()
+}
+
+pub async fn post_images(
+    State(state): State<AppState>,
+    user: AuthUser,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let Some(name) = field.name().map(|name| name.to_string()) else {
+            continue;
+        };
+        let media_type = field.content_type().unwrap_or("").to_string();
+        let data = match media::read_field(&state, field).await {
+            Ok(data) => data,
+            Err(e) => return e.into_response(),
+        };
+        // Empty file inputs leave the image unchanged
+        if data.is_empty() {
+            continue;
+        }
+        let res = match name.as_str() {
+            "avatar" => user::update_avatar(&state, user.id, &media_type, &data).await,
+            "header" => user::update_header(&state, user.id, &media_type, &data).await,
+            _ => Ok(()),
+        };
+        if let Err(e) = res {
+            return e.into_response();
+        }
+    }
+
+    Redirect::to("/profile").into_response()
+}
+
+#[derive(serde::Deserialize)]
+pub struct AliasesForm {
+    pub aliases: String,
+}
+
+pub async fn post_aliases(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Form(form): Form<AliasesForm>,
+) -> impl IntoResponse {
+    if let Err(e) = user::update_aliases(&state, user.id, &form.aliases).await {
+        return e.into_response();
+    }
+
+    Redirect::to("/profile").into_response()
+}
+
+#[derive(serde::Deserialize)]
+pub struct MoveForm {
+    pub target: String,
+    pub password: String,
+}
+
+pub async fn post_move(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Form(form): Form<MoveForm>,
+) -> impl IntoResponse {
+    // Verify password
+    let user = queries::user::get_by_id(&state, user.id).await;
+    let verify = user::verify_password(&state, &user.username, &form.password).await;
+    if verify.is_err() {
+        return "password is incorrect.".into_response();
+    }
+
+    // Move
+    if let Err(e) = user::move_to(&state, user.id, &form.target).await {
+        return e.into_response();
+    }
+
+    // Deliver move activity
+    user::deliver_move(&state, user.id).await;
+
+    Redirect::to("/profile").into_response()
+}
+
 #[derive(serde::Deserialize)]
 pub struct ChangePasswordForm {
     pub current_password: String,
@@ -76,6 +200,32 @@ pub async fn post_password(
     Redirect::to("/login").into_response()
 }
 
+#[derive(serde::Deserialize)]
+pub struct DeleteAccountForm {
+    pub password: String,
+}
+
+pub async fn post_delete_account(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Form(form): Form<DeleteAccountForm>,
+) -> impl IntoResponse {
+    // Verify password
+    let user = queries::user::get_by_id(&state, user.id).await;
+    let verify = user::verify_password(&state, &user.username, &form.password).await;
+    if verify.is_err() {
+        return "password is incorrect.".into_response();
+    }
+
+    // Deliver delete activity
+    user::deliver_delete(&state, user.id).await;
+
+    // Delete
+    user::delete(&state, user.id).await;
+
+    Redirect::to("/login").into_response()
+}
+
 #[cfg(feature = "api")]
 #[derive(serde::Deserialize)]
 pub struct RevokeTokenForm {
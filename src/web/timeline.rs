@@ -1,10 +1,12 @@
+use crate::back::attachment;
 use crate::back::init::AppState;
 use crate::back::queries;
+use crate::back::quote;
 use crate::back::utils::extract_until_id;
 use crate::web::auth::AuthUser;
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     response::Html,
 };
 
@@ -23,7 +25,7 @@ pub async fn get_home(
 
     // Get notes
     let (until_date, until_id) = extract_until_id(&state, query.until).await;
-    let notes = queries::timeline::get_home(
+    let mut notes = queries::timeline::get_home(
         &state,
         user.id,
         &until_date,
@@ -31,6 +33,8 @@ pub async fn get_home(
         state.web_config.max_timeline_items,
     )
     .await;
+    attachment::fill(&state, &mut notes).await;
+    quote::fill(&state, &mut notes).await;
     let until_next = if let Some(last_note) = notes.last() {
         last_note.id
     } else {
@@ -56,13 +60,15 @@ pub async fn get_local(
     Query(query): Query<PageQuery>,
 ) -> Html<String> {
     let (until_date, until_id) = extract_until_id(&state, query.until).await;
-    let notes = queries::timeline::get_local(
+    let mut notes = queries::timeline::get_local(
         &state,
         &until_date,
         until_id,
         state.web_config.max_timeline_items,
     )
     .await;
+    attachment::fill(&state, &mut notes).await;
+    quote::fill(&state, &mut notes).await;
     let until_next = if let Some(last_note) = notes.last() {
         last_note.id
     } else {
@@ -86,13 +92,15 @@ pub async fn get_federated(
     Query(query): Query<PageQuery>,
 ) -> Html<String> {
     let (until_date, until_id) = extract_until_id(&state, query.until).await;
-    let notes = queries::timeline::get_federated(
+    let mut notes = queries::timeline::get_federated(
         &state,
         &until_date,
         until_id,
         state.web_config.max_timeline_items,
     )
     .await;
+    attachment::fill(&state, &mut notes).await;
+    quote::fill(&state, &mut notes).await;
     let until_next = if let Some(last_note) = notes.last() {
         last_note.id
     } else {
@@ -110,3 +118,38 @@ pub async fn get_federated(
 
     Html(rendered)
 }
+
+pub async fn get_tag(
+    State(state): State<AppState>,
+    Path(hashtag): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Html<String> {
+    let hashtag = hashtag.trim_start_matches('#').to_lowercase();
+    let (until_date, until_id) = extract_until_id(&state, query.until).await;
+    let mut notes = queries::timeline::get_tag(
+        &state,
+        &hashtag,
+        &until_date,
+        until_id,
+        state.web_config.max_timeline_items,
+    )
+    .await;
+    attachment::fill(&state, &mut notes).await;
+    quote::fill(&state, &mut notes).await;
+    let until_next = if let Some(last_note) = notes.last() {
+        last_note.id
+    } else {
+        until_id
+    };
+
+    let mut context = tera::Context::new();
+    context.insert("instance_name", &state.metadata.instance_name);
+    context.insert("title", &format!("#{}", hashtag));
+    context.insert("timezone", &state.web_config.timezone);
+    context.insert("notes", &notes);
+    context.insert("until_next", &until_next);
+    context.insert("max_notes", &state.web_config.max_timeline_items);
+    let rendered = state.tera.render("timeline.html", &context).unwrap();
+
+    Html(rendered)
+}
@@ -1,5 +1,7 @@
+use crate::back::attachment;
 use crate::back::init::AppState;
 use crate::back::queries;
+use crate::back::quote;
 use crate::back::utils::extract_until_id;
 use crate::web::auth::MaybeAuthUser;
 
@@ -35,14 +37,26 @@ pub async fn get(
 
     // Get notes by user
     let (until_date, until_id) = extract_until_id(&state, query.until).await;
-    let notes = queries::timeline::get_user(
+    let mut notes = queries::timeline::get_user(
         &state,
         user.id,
+        auth_user.id.unwrap_or(0),
         &until_date,
         until_id,
         state.web_config.max_timeline_items,
     )
     .await;
+    attachment::fill(&state, &mut notes).await;
+    quote::fill(&state, &mut notes).await;
+
+    // Pinned notes on the first page
+    let mut pinned_notes = if query.until.is_none() {
+        queries::timeline::get_pinned(&state, user.id, auth_user.id.unwrap_or(0)).await
+    } else {
+        vec![]
+    };
+    attachment::fill(&state, &mut pinned_notes).await;
+    quote::fill(&state, &mut pinned_notes).await;
     let until_next = if let Some(last_note) = notes.last() {
         last_note.id
     } else {
@@ -71,13 +85,27 @@ pub async fn get(
         following_status = 0;
     }
 
+    let fields = queries::profile_field::get_by_user(&state, user.id).await;
+
+    // Moved account
+    let moved_to = match &user.moved_to {
+        Some(moved_to) => queries::user::get_by_ap_url(&state, moved_to).await,
+        None => None,
+    };
+
     // Rendering
     let mut context = tera::Context::new();
     context.insert("instance_name", &state.metadata.instance_name);
     context.insert("timezone", &state.web_config.timezone);
     context.insert("domain", &state.domain);
     context.insert("user", &user);
+    context.insert("fields", &fields);
+    context.insert(
+        "moved_to_username",
+        &moved_to.map(|moved_to| moved_to.username),
+    );
     context.insert("following_status", &following_status);
+    context.insert("pinned_notes", &pinned_notes);
     context.insert("notes", &notes);
     context.insert("until_next", &until_next);
     context.insert("max_notes", &state.web_config.max_timeline_items);